@@ -11,4 +11,185 @@ redo-hover-text = Redo: {$operation}
 undo-hover-text = Undo: {$operation}
 save-hover-text = Save changes
 scenery-pack-disabled-operation = Scenery pack {$path} disabled
-scenery-pack-enabled-operation = Scenery pack {$path} enabled
\ No newline at end of file
+scenery-pack-enabled-operation = Scenery pack {$path} enabled
+scanning-scenery-packs-label = Scanning scenery packs…
+rescan-hover-text = Scan scenery packs again
+requires-libraries-label = Requires libraries:
+required-by-label = Required by:
+dependency-hover-line = • {$pack} ({$count} paths)
+unresolved-paths-label = {$count} referenced paths not found in any library
+libraries-enabled-operation = also enabled {$paths}
+libraries-disabled-operation = also disabled {$paths}
+manage-libraries-checkbox-label = Manage libraries
+manage-libraries-hover-text = When enabling or disabling a scenery pack, automatically enable the libraries it requires, and disable the libraries which are no longer required
+category-airport = Airport
+category-plugin-driven = Plugin
+category-overlay = Overlay
+category-library = Library
+category-ortho = Ortho
+category-mesh = Mesh
+category-filter-all = All categories
+category-filter-hover-text = Only show scenery packs in this category
+presets-heading = Presets
+preset-applied-operation = Preset {$name} applied
+preset-name-hint = New preset name
+create-preset-button = Create
+create-preset-hover-text = Create a preset from the current scenery packs
+apply-preset-hover-text = Apply this preset to the scenery packs
+update-preset-hover-text = Replace this preset with the current scenery packs
+rename-preset-hover-text = Rename this preset
+duplicate-preset-hover-text = Duplicate this preset
+delete-preset-hover-text = Delete this preset
+preset-copy-name = {$name} copy
+area-enabled-operation = Enabled scenery in area ({$enabled} enabled, {$disabled} disabled)
+area-heading = Enable by area
+area-circle-option = Around a point
+area-box-option = Bounding box
+area-airport-label = Airport
+area-airport-hint = ICAO
+area-airport-not-found = Airport not found in any scanned scenery pack
+area-latitude-label = Latitude
+area-longitude-label = Longitude
+area-radius-label = Radius (nm)
+area-south-label = South
+area-west-label = West
+area-north-label = North
+area-east-label = East
+area-enable-button = Enable only scenery in area
+area-enable-hover-text = Enable the scenery packs which cover this area, and disable the scenery packs which cover other areas. Libraries are enabled or disabled as required.
+route-heading = Enable along route
+route-hint = Airports, fixes or lat/lon, e.g. NZQN TEKAP -43.5/172.5
+route-open-fms-hover-text = Read the route from an X-Plane flight plan (.fms)
+route-fms-label = Flight plan {$file} ({$count} waypoints)
+route-clear-fms-hover-text = Use the route entered above instead of the flight plan
+route-width-label = Corridor width (nm)
+route-enable-button = Enable only scenery along route
+route-enable-hover-text = Enable the scenery packs which cover the route, and disable the scenery packs which cover other areas. Libraries are enabled or disabled as required.
+scenery-packs-added-operation = Added {$count} scenery packs
+scenery-packs-removed-operation = Removed {$count} scenery packs
+new-scenery-packs-heading = {$count} new scenery packs are installed
+removed-scenery-packs-heading = {$count} scenery packs are no longer installed
+add-scenery-pack-hover-text = Add this scenery pack, at the position suited to its category
+add-all-scenery-packs-button = Add all
+add-all-scenery-packs-hover-text = Add all the new scenery packs, each at the position suited to its category
+remove-scenery-pack-hover-text = Remove this scenery pack from the list
+remove-all-scenery-packs-button = Remove all
+remove-all-scenery-packs-hover-text = Remove all the scenery packs which are no longer installed from the list
+scenery-pack-missing-label = Missing
+scenery-pack-missing-hover-text = This scenery pack is no longer installed
+scenery-packs-enabled-operation = Enabled {$count} scenery packs
+scenery-packs-disabled-operation = Disabled {$count} scenery packs
+scenery-packs-moved-operation = Moved {$count} scenery packs
+scenery-packs-tagged-operation = Tagged {$count} scenery packs with {$tag}
+scenery-packs-untagged-operation = Removed tag {$tag} from {$count} scenery packs
+selected-scenery-packs-label = {$count} selected
+select-all-button = All
+select-none-button = None
+select-invert-button = Invert
+enable-selected-button = Enable
+enable-selected-hover-text = Enable the selected scenery packs
+disable-selected-button = Disable
+disable-selected-hover-text = Disable the selected scenery packs
+move-selected-button = Move to
+move-selected-hover-text = Move the selected scenery packs to this position, keeping their order
+tag-hint = Tag
+tag-selected-hover-text = Add this tag to the selected scenery packs
+untag-selected-hover-text = Remove this tag from the selected scenery packs
+add-selected-to-preset-button = Add to preset
+move-selected-top-hover-text = Move the selected scenery packs to the top of the list
+move-selected-up-hover-text = Move the selected scenery packs up one place
+move-selected-down-hover-text = Move the selected scenery packs down one place
+move-selected-bottom-hover-text = Move the selected scenery packs to the bottom of the list
+drag-scenery-packs-hover-text = Drag to move the selected scenery packs
+scenery-packs-sorted-operation = Sorted scenery packs
+sort-button = Sort
+sort-hover-text = Sort the load order of the scenery packs by category
+sort-heading = Sort scenery packs
+sort-rules-heading = Load order
+sort-rule-global-airports = Global airports
+sort-rule-up-hover-text = Load these scenery packs earlier
+sort-rule-down-hover-text = Load these scenery packs later
+sort-rule-remove-hover-text = Remove this rule
+sort-rule-add-button = Add rule
+sort-rules-reset-button = Reset
+sort-rules-reset-hover-text = Reset to the conventional load order
+sort-preview-unchanged-label = The scenery packs are already in this order
+sort-preview-heading = {$count} scenery packs will move
+sort-apply-button = Apply
+sort-apply-hover-text = Sort the scenery packs into this order
+sort-cancel-button = Cancel
+pending-changes-heading = {$count} unsaved changes
+change-added-label = Added
+change-removed-label = Removed
+change-enabled-label = Enabled
+change-disabled-label = Disabled
+change-moved-label = Moved {$from} → {$to}
+change-hover-text = Changed since scenery_packs.ini was saved
+scenery-packs-merged-operation = Merged changes to scenery_packs.ini
+ini-modified-label = scenery_packs.ini was modified by another program.
+ini-reload-button = Reload
+ini-reload-hover-text = Read the modified scenery_packs.ini, discarding unsaved changes
+ini-merge-button = Merge
+ini-merge-hover-text = Combine the changes made by the other program with the unsaved changes
+ini-overwrite-button = Overwrite
+ini-overwrite-hover-text = Save the current scenery packs, discarding the changes made by the other program
+ini-merge-removal-conflicts-label = The changes conflicted: {$count} scenery packs removed on one side were changed on the other. Unsaved changes were kept.
+ini-merge-order-conflict-label = The changes conflicted: both sides reordered the scenery packs. The unsaved order was kept.
+backups-heading = Backups
+backup-count-label = Backups to keep
+no-backups-label = No backups yet. scenery_packs.ini is backed up each time it is saved.
+restore-backup-button = Restore
+restore-backup-hover-text = Replace the scenery packs with this backup. This can be undone, and is not saved until you save.
+backup-restored-operation = Restored backup from {$time}
+scenery-packs-page-title = Scenery packs
+backups-page-title = Backups
+refresh-backups-hover-text = Refresh the list of backups
+backup-time-column = Saved
+backup-enabled-column = Enabled
+backup-disabled-column = Disabled
+backup-changes-column = Changes if restored
+backup-unchanged-label = Same as current
+backup-changes-label = {$count} changes
+backup-changes-hover-text = Show how restoring this backup would change the current scenery packs
+global-airports-label = 🌐 Global Airports
+global-airports-missing-label = This version of X-Plane only loads its global airports through the *GLOBAL_AIRPORTS* entry, which is missing from the scenery packs.
+add-global-airports-button = Add *GLOBAL_AIRPORTS*
+add-global-airports-hover-text = Add the *GLOBAL_AIRPORTS* entry, after the custom airports
+absolute-scenery-pack-label = 🔗 {$path}
+invalid-lines-label = {$count ->
+    [one] A line in scenery_packs.ini could not be read. Choose whether to keep it as it is or remove it before saving.
+   *[other] {$count} lines in scenery_packs.ini could not be read. Choose whether to keep them as they are or remove them before saving.
+}
+invalid-line-label = Line {$line}, column {$column}: expected {$expected}
+keep-invalid-lines-button = Keep
+keep-invalid-lines-hover-text = Write the lines back to scenery_packs.ini exactly as they are
+remove-invalid-lines-button = Remove
+remove-invalid-lines-hover-text = Leave the lines out when scenery_packs.ini is saved
+save-invalid-lines-hover-text = Choose what to do with the lines which could not be read before saving
+scenery-packs-ini-version-hover-text = The installed version of X-Plane. scenery_packs.ini has version {$version}.
+airport-conflicts-heading = {$count} airports are provided by more than one enabled scenery pack
+airport-conflict-label = {$ident}:
+airport-conflict-winner-hover-text = X-Plane uses the airport from this scenery pack, because it is loaded first
+airport-conflict-loser-hover-text = X-Plane ignores the airport from this scenery pack
+prefer-airport-provider-hover-text = Move this scenery pack above the others, so that X-Plane uses its airport
+disable-all-airport-losers-button = Disable ignored
+disable-all-airport-losers-hover-text = Disable the scenery packs whose airports are ignored in favour of another scenery pack's
+disable-airport-losers-hover-text = Disable the scenery packs whose copy of this airport is ignored
+airport-conflicts-resolved-operation = Disabled scenery packs with ignored airports for {$count} airports
+airport-provider-preferred-operation = Preferred {$path} for airport {$ident}
+airports-page-title = Airports
+airports-heading = Airports
+airport-search-hint = Search by ICAO or name
+airport-region-all = All regions
+airport-region-hover-text = Only show airports in this region
+airport-count-label = {$count} airports
+no-airports-label = No airports found. Airports are listed once the scenery packs have been scanned.
+airport-enabled-column = Enabled
+airport-icao-column = ICAO
+airport-name-column = Name
+airport-region-column = Region
+airport-providers-column = Provided by
+airport-enable-hover-text = Enable the scenery pack which provides this airport, along with the libraries it requires. Disabling the airport disables every custom scenery pack which provides it, but never the global airports.
+airport-not-listed-hover-text = None of the custom scenery packs which provide this airport are in scenery_packs.ini. Airports which only the global airports provide are always available while they are enabled.
+airport-enabled-operation = Airport {$ident} enabled
+airport-disabled-operation = Airport {$ident} disabled
@@ -0,0 +1,392 @@
+//! The airports provided by scenery packs, and the conflicts between
+//! enabled scenery packs which provide the same airport.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    parsers::scenery_packs::{SceneryPack, SceneryPackPath},
+    scan::SceneryScan,
+};
+
+/// An airport, and the scenery packs which provide it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirportEntry {
+    /// The airport's identifier, see
+    /// [Airport::ident](crate::parsers::apt_dat::Airport::ident).
+    pub ident: String,
+    /// The airport's ICAO code, see
+    /// [Airport::icao()](crate::parsers::apt_dat::Airport::icao).
+    pub icao: String,
+    pub name: String,
+    pub region: Option<String>,
+    /// The scenery packs whose `apt.dat` defines the airport.
+    pub providers: BTreeSet<PathBuf>,
+}
+
+impl AirportEntry {
+    /// Whether the airport matches a search for `text`, by its
+    /// identifier, ICAO code or name, ignoring case. `text` must be
+    /// lower case.
+    pub fn matches(&self, text: &str) -> bool {
+        [&self.ident, &self.icao, &self.name]
+            .iter()
+            .any(|field| field.to_lowercase().contains(text))
+    }
+
+    /// The scenery packs in `scenery_packs` which provide the airport,
+    /// with their indices, in load order. As in
+    /// [AirportIndex::conflicts()], the global airports are not
+    /// included, so that toggling an airport never toggles every
+    /// default airport in the world.
+    fn custom_providers<'a>(
+        &'a self,
+        scenery_packs: &'a im_rc::Vector<SceneryPack>,
+    ) -> impl Iterator<Item = (usize, &'a SceneryPack)> + 'a {
+        scenery_packs
+            .iter()
+            .enumerate()
+            .filter(move |(_, scenery_pack)| {
+                self.providers.contains(&scenery_pack.path) && !is_global_airports(scenery_pack)
+            })
+    }
+
+    /// Whether any of the scenery packs in `scenery_packs` which
+    /// provide the airport, other than the global airports, are listed,
+    /// so that it can be enabled or disabled.
+    pub fn is_listed(&self, scenery_packs: &im_rc::Vector<SceneryPack>) -> bool {
+        self.custom_providers(scenery_packs).next().is_some()
+    }
+
+    /// Whether any of the scenery packs in `scenery_packs` which
+    /// provide the airport, other than the global airports, are
+    /// enabled.
+    pub fn is_enabled(&self, scenery_packs: &im_rc::Vector<SceneryPack>) -> bool {
+        self.custom_providers(scenery_packs)
+            .any(|(_, scenery_pack)| scenery_pack.enabled)
+    }
+
+    /// The changes (pairs of index and enabled state, see
+    /// [DependencyGraph::set_enabled_many()](crate::dependencies::DependencyGraph::set_enabled_many))
+    /// to `scenery_packs` which enable or disable the airport.
+    ///
+    /// Enabling the airport enables only the scenery pack which
+    /// provides it that is highest in the load order. Disabling it
+    /// disables all the scenery packs which provide it. The global
+    /// airports are left untouched either way.
+    pub fn enable_changes(
+        &self,
+        scenery_packs: &im_rc::Vector<SceneryPack>,
+        enabled: bool,
+    ) -> Vec<(usize, bool)> {
+        let providers = self.custom_providers(scenery_packs);
+        if enabled {
+            providers.map(|(index, _)| (index, true)).take(1).collect()
+        } else {
+            providers
+                .filter(|(_, scenery_pack)| scenery_pack.enabled)
+                .map(|(index, _)| (index, false))
+                .collect()
+        }
+    }
+}
+
+/// An index of the airports provided by scenery packs, calculated from
+/// a [SceneryScan]. Airports are identified by their identifier,
+/// ignoring case, as X-Plane does.
+#[derive(Clone, Debug, Default)]
+pub struct AirportIndex {
+    airports: BTreeMap<String, AirportEntry>,
+}
+
+impl AirportIndex {
+    pub fn new(scan: &SceneryScan) -> Self {
+        let mut airports: BTreeMap<String, AirportEntry> = BTreeMap::new();
+        // The details of an airport are taken from the first scenery
+        // pack by path which provides it, so that they do not depend
+        // on the order of the scan.
+        let packs: BTreeMap<_, _> = scan.packs.iter().collect();
+        for (pack_path, pack_scan) in packs {
+            for airport in &pack_scan.airports {
+                airports
+                    .entry(airport.ident.to_uppercase())
+                    .or_insert_with(|| AirportEntry {
+                        ident: airport.ident.clone(),
+                        icao: airport.icao().to_string(),
+                        name: airport.name.clone(),
+                        region: airport.region.clone(),
+                        providers: BTreeSet::new(),
+                    })
+                    .providers
+                    .insert(pack_path.clone());
+            }
+        }
+        Self { airports }
+    }
+
+    /// All the airports, in order of their identifiers.
+    pub fn iter(&self) -> impl Iterator<Item = &AirportEntry> {
+        self.airports.values()
+    }
+
+    pub fn get(&self, ident: &str) -> Option<&AirportEntry> {
+        self.airports.get(&ident.to_uppercase())
+    }
+
+    /// The regions of all the airports which have one.
+    pub fn regions(&self) -> BTreeSet<&str> {
+        self.airports
+            .values()
+            .filter_map(|airport| airport.region.as_deref())
+            .collect()
+    }
+
+    /// The airports which are provided by more than one enabled scenery
+    /// pack in `scenery_packs`, in order of their identifiers.
+    ///
+    /// The global airports are not included as a provider, because
+    /// custom airports are expected to replace them.
+    pub fn conflicts(&self, scenery_packs: &im_rc::Vector<SceneryPack>) -> Vec<AirportConflict> {
+        let positions: HashMap<&Path, usize> = scenery_packs
+            .iter()
+            .enumerate()
+            .filter(|(_, scenery_pack)| scenery_pack.enabled && !is_global_airports(scenery_pack))
+            .map(|(index, scenery_pack)| (scenery_pack.path.as_path(), index))
+            .collect();
+
+        self.airports
+            .values()
+            .filter_map(|airport| {
+                let mut providers: Vec<(usize, &PathBuf)> = airport
+                    .providers
+                    .iter()
+                    .filter_map(|path| Some((*positions.get(path.as_path())?, path)))
+                    .collect();
+                if providers.len() < 2 {
+                    return None;
+                }
+                providers.sort();
+                Some(AirportConflict {
+                    ident: airport.ident.clone(),
+                    providers: providers
+                        .into_iter()
+                        .map(|(_, path)| path.clone())
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whether the scenery pack is the global airports, either as the
+/// `*GLOBAL_AIRPORTS*` entry or as `Custom Scenery/Global Airports/`.
+fn is_global_airports(scenery_pack: &SceneryPack) -> bool {
+    match scenery_pack.location() {
+        SceneryPackPath::GlobalAirports => true,
+        SceneryPackPath::Relative(path) => path == Path::new("Custom Scenery/Global Airports"),
+        _ => false,
+    }
+}
+
+/// An airport which is provided by more than one enabled scenery pack.
+/// X-Plane uses the airport from the scenery pack which is loaded
+/// first, i.e. the one highest in `scenery_packs.ini`, and ignores the
+/// others.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirportConflict {
+    pub ident: String,
+    /// The enabled scenery packs which provide the airport, in load
+    /// order.
+    pub providers: Vec<PathBuf>,
+}
+
+impl AirportConflict {
+    /// The scenery pack whose airport is used.
+    pub fn winner(&self) -> &Path {
+        &self.providers[0]
+    }
+
+    /// The scenery packs whose airport is ignored.
+    pub fn losers(&self) -> &[PathBuf] {
+        &self.providers[1..]
+    }
+}
+
+/// Disable the scenery packs which lose any of `conflicts`.
+pub fn disable_losers(
+    scenery_packs: &im_rc::Vector<SceneryPack>,
+    conflicts: &[AirportConflict],
+) -> im_rc::Vector<SceneryPack> {
+    let losers: BTreeSet<&PathBuf> = conflicts
+        .iter()
+        .flat_map(|conflict| conflict.losers())
+        .collect();
+    scenery_packs
+        .iter()
+        .map(|scenery_pack| {
+            let mut scenery_pack = scenery_pack.clone();
+            if losers.contains(&scenery_pack.path) {
+                scenery_pack.enabled = false;
+            }
+            scenery_pack
+        })
+        .collect()
+}
+
+/// Move the scenery pack at `path` directly above the winner of
+/// `conflict`, so that its airport is used instead.
+pub fn prefer_provider(
+    scenery_packs: &im_rc::Vector<SceneryPack>,
+    conflict: &AirportConflict,
+    path: &Path,
+) -> im_rc::Vector<SceneryPack> {
+    let mut scenery_packs = scenery_packs.clone();
+    let index = scenery_packs
+        .iter()
+        .position(|scenery_pack| scenery_pack.path == path);
+    let winner_index = scenery_packs
+        .iter()
+        .position(|scenery_pack| scenery_pack.path == conflict.winner());
+    if let (Some(index), Some(winner_index)) = (index, winner_index) {
+        if index > winner_index {
+            let scenery_pack = scenery_packs.remove(index);
+            scenery_packs.insert(winner_index, scenery_pack);
+        }
+    }
+    scenery_packs
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{disable_losers, prefer_provider, AirportConflict, AirportIndex};
+    use crate::{
+        parsers::{
+            apt_dat::{Airport, AirportKind},
+            scenery_packs::SceneryPack,
+        },
+        scan::{SceneryPackScan, SceneryScan},
+    };
+
+    fn airport(ident: &str) -> Airport {
+        Airport {
+            kind: AirportKind::Airport,
+            ident: ident.to_string(),
+            name: String::new(),
+            elevation: 0,
+            runways: Vec::new(),
+            datum: None,
+            icao_code: None,
+            region: None,
+        }
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut scan = SceneryScan::default();
+        for (path, idents) in [
+            ("Custom Scenery/NZQN A/", &["NZQN", "NZWF"][..]),
+            ("Custom Scenery/NZQN B/", &["nzqn"]),
+            ("Custom Scenery/NZQN C/", &["NZQN"]),
+            ("Custom Scenery/NZWF/", &["NZWF"]),
+            ("*GLOBAL_AIRPORTS*", &["NZQN", "NZWF"]),
+        ] {
+            scan.packs.insert(
+                path.into(),
+                SceneryPackScan {
+                    airports: idents.iter().map(|ident| airport(ident)).collect(),
+                    ..Default::default()
+                },
+            );
+        }
+        let index = AirportIndex::new(&scan);
+
+        let scenery_packs: im_rc::Vector<SceneryPack> = vec![
+            SceneryPack::new("Custom Scenery/NZQN B/", true),
+            SceneryPack::new("Custom Scenery/NZQN A/", true),
+            SceneryPack::new("Custom Scenery/NZQN C/", true),
+            SceneryPack::new("Custom Scenery/NZWF/", false),
+            SceneryPack::new("*GLOBAL_AIRPORTS*", true),
+        ]
+        .into();
+        let conflicts = index.conflicts(&scenery_packs);
+        assert_eq!(
+            vec![AirportConflict {
+                ident: "NZQN".to_string(),
+                providers: vec![
+                    PathBuf::from("Custom Scenery/NZQN B/"),
+                    PathBuf::from("Custom Scenery/NZQN A/"),
+                    PathBuf::from("Custom Scenery/NZQN C/"),
+                ],
+            }],
+            conflicts
+        );
+
+        let disabled = disable_losers(&scenery_packs, &conflicts);
+        assert_eq!(
+            vec![true, false, false, false, true],
+            disabled
+                .iter()
+                .map(|scenery_pack| scenery_pack.enabled)
+                .collect::<Vec<_>>()
+        );
+
+        let preferred = prefer_provider(
+            &scenery_packs,
+            &conflicts[0],
+            &PathBuf::from("Custom Scenery/NZQN C/"),
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("Custom Scenery/NZQN C/"),
+                PathBuf::from("Custom Scenery/NZQN B/"),
+                PathBuf::from("Custom Scenery/NZQN A/"),
+            ],
+            index.conflicts(&preferred)[0].providers
+        );
+    }
+
+    #[test]
+    fn test_enable_changes() {
+        let mut scan = SceneryScan::default();
+        for (path, idents) in [
+            ("Custom Scenery/LOWI A/", &["LOWI"][..]),
+            ("Custom Scenery/LOWI B/", &["LOWI"]),
+            ("*GLOBAL_AIRPORTS*", &["LOWI", "LOWS"]),
+        ] {
+            scan.packs.insert(
+                path.into(),
+                SceneryPackScan {
+                    airports: idents.iter().map(|ident| airport(ident)).collect(),
+                    ..Default::default()
+                },
+            );
+        }
+        let index = AirportIndex::new(&scan);
+        let scenery_packs: im_rc::Vector<SceneryPack> = vec![
+            SceneryPack::new("*GLOBAL_AIRPORTS*", true),
+            SceneryPack::new("Custom Scenery/LOWI B/", false),
+            SceneryPack::new("Custom Scenery/LOWI A/", true),
+        ]
+        .into();
+
+        let lowi = index.get("lowi").unwrap();
+        assert!(lowi.is_listed(&scenery_packs));
+        assert!(lowi.is_enabled(&scenery_packs));
+        assert_eq!(vec![(1, true)], lowi.enable_changes(&scenery_packs, true));
+        assert_eq!(vec![(2, false)], lowi.enable_changes(&scenery_packs, false));
+
+        // Only provided by the global airports, which are enabled.
+        let lows = index.get("LOWS").unwrap();
+        assert!(!lows.is_listed(&scenery_packs));
+        assert!(!lows.is_enabled(&scenery_packs));
+        assert!(lows.enable_changes(&scenery_packs, true).is_empty());
+        assert!(lows.enable_changes(&scenery_packs, false).is_empty());
+        assert!(index.get("NZQN").is_none());
+    }
+}
@@ -2,19 +2,84 @@ use std::rc::Rc;
 
 use crate::{
     fl,
+    presets::Presets,
     settings::Settings,
     state::{ScenableAction, ScenableReducer, ScenableState, ScenableStateRef},
+    tags::Tags,
     view::page::{
+        airports::AirportsPage,
+        backups::BackupsPage,
         scenery_packs::SceneryPacksPage,
         setup::{SetupPage, SetupPageResult},
         Page,
     },
+    xplane::XPlaneVersion,
 };
 use epi::App;
 use reactive_state::middleware::simple_logger::SimpleLoggerMiddleware;
 enum ScenablePage {
     Setup(SetupPage),
-    SceneryPacks(SceneryPacksPage),
+    Main(Box<MainPages>),
+}
+
+/// The pages which the user can navigate between once setup is
+/// complete. They are kept alive while other pages are shown, so that
+/// they retain their state.
+struct MainPages {
+    current: MainPage,
+    scenery_packs: SceneryPacksPage,
+    airports: AirportsPage,
+    backups: BackupsPage,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MainPage {
+    SceneryPacks,
+    Airports,
+    Backups,
+}
+
+impl MainPages {
+    fn new(state: ScenableStateRef) -> Self {
+        Self {
+            current: MainPage::SceneryPacks,
+            scenery_packs: SceneryPacksPage::new(state.clone()),
+            airports: AirportsPage::new(state.clone()),
+            backups: BackupsPage::new(state),
+        }
+    }
+
+    fn show(&mut self, ctx: &egui::CtxRef, frame: &epi::Frame) {
+        egui::TopBottomPanel::top("navigation-panel").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let previous = self.current;
+                ui.selectable_value(
+                    &mut self.current,
+                    MainPage::SceneryPacks,
+                    fl!("scenery-packs-page-title"),
+                );
+                ui.selectable_value(
+                    &mut self.current,
+                    MainPage::Airports,
+                    fl!("airports-page-title"),
+                );
+                ui.selectable_value(
+                    &mut self.current,
+                    MainPage::Backups,
+                    fl!("backups-page-title"),
+                );
+                if self.current == MainPage::Backups && previous != MainPage::Backups {
+                    self.backups.refresh();
+                }
+            });
+        });
+
+        match self.current {
+            MainPage::SceneryPacks => self.scenery_packs.show(ctx, frame),
+            MainPage::Airports => self.airports.show(ctx, frame),
+            MainPage::Backups => self.backups.show(ctx, frame),
+        }
+    }
 }
 
 pub struct ScenableApp {
@@ -34,8 +99,26 @@ impl Default for ScenableApp {
             }
         };
 
+        let presets = Presets::from_presets_file().unwrap_or_else(|error| {
+            tracing::error!("Error reading presets from presets file: {}", error);
+            Default::default()
+        });
+
+        let tags = Tags::from_tags_file().unwrap_or_else(|error| {
+            tracing::error!("Error reading tags from tags file: {}", error);
+            Default::default()
+        });
+
+        let xplane_version = settings
+            .xplane_dir
+            .as_deref()
+            .and_then(XPlaneVersion::detect);
+
         let state = ScenableState {
             settings: Rc::new(settings),
+            presets: Rc::new(presets),
+            tags: Rc::new(tags),
+            xplane_version,
             ..Default::default()
         };
 
@@ -46,7 +129,7 @@ impl Default for ScenableApp {
         let page = if setup_required {
             ScenablePage::Setup(Default::default())
         } else {
-            ScenablePage::SceneryPacks(SceneryPacksPage::new(state.clone()))
+            ScenablePage::Main(Box::new(MainPages::new(state.clone())))
         };
 
         Self {
@@ -67,13 +150,18 @@ impl App for ScenableApp {
                     if let Err(error) = settings.save() {
                         tracing::error!("Error while saving settings: {}", error);
                     }
+                    let xplane_version = settings
+                        .xplane_dir
+                        .as_deref()
+                        .and_then(XPlaneVersion::detect);
                     self.state
                         .dispatch(ScenableAction::UpdateSettings(settings));
-                    self.page =
-                        ScenablePage::SceneryPacks(SceneryPacksPage::new(self.state.clone()));
+                    self.state
+                        .dispatch(ScenableAction::UpdateXPlaneVersion(xplane_version));
+                    self.page = ScenablePage::Main(Box::new(MainPages::new(self.state.clone())));
                 }
             }
-            ScenablePage::SceneryPacks(page) => page.show(ctx, frame),
+            ScenablePage::Main(pages) => pages.show(ctx, frame),
         }
     }
 
@@ -0,0 +1,256 @@
+//! Safe writing of `scenery_packs.ini`, and backups of its previous
+//! versions.
+
+use std::{
+    io::Write,
+    os::unix::prelude::OsStrExt,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use eyre::Context;
+
+use crate::settings::Settings;
+
+const BACKUP_PREFIX: &str = "scenery_packs-";
+const BACKUP_EXTENSION: &str = ".ini";
+/// The format of the time in backup file names, which sorts
+/// chronologically.
+const BACKUP_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// Write `contents` to the file at `path` without the risk of leaving
+/// it partially written: the contents are written to a temporary file
+/// in the same directory, flushed to disk, and then renamed over the
+/// original. The original's permissions are kept.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> eyre::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| eyre::eyre!("Path has no file name: {:?}", path))?;
+    let mut temp_file_name = file_name.to_owned();
+    temp_file_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_file_name);
+
+    let write_temp = || -> eyre::Result<()> {
+        let mut file = std::fs::File::create(&temp_path)?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    };
+
+    if let Err(error) = write_temp() {
+        if temp_path.exists() {
+            if let Err(remove_error) = std::fs::remove_file(&temp_path) {
+                tracing::error!(
+                    "Unable to remove temporary file {:?}: {}",
+                    temp_path,
+                    remove_error
+                );
+            }
+        }
+        return Err(error).wrap_err_with(|| eyre::eyre!("Unable to write {:?}", path));
+    }
+
+    // Ensure that the rename itself is persisted.
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// A backup of a previous version of `scenery_packs.ini`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// When the backup was created.
+    pub time: DateTime<Utc>,
+}
+
+/// A directory of timestamped backups of one X-Plane installation's
+/// `scenery_packs.ini`.
+pub struct Backups {
+    dir: PathBuf,
+}
+
+impl Backups {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The backups of the `scenery_packs.ini` at `ini_path`, stored in
+    /// a subdirectory of `backups_dir` named after a hash of its
+    /// canonical path, so that each X-Plane installation has its own
+    /// backups.
+    pub fn for_ini(backups_dir: &Path, ini_path: &Path) -> Self {
+        let ini_path = ini_path
+            .canonicalize()
+            .unwrap_or_else(|_| ini_path.to_path_buf());
+        let digest = md5::compute(ini_path.as_os_str().as_bytes());
+        Self::new(backups_dir.join(format!("{:x}", digest)))
+    }
+
+    /// The backups of the `scenery_packs.ini` at `ini_path`, stored in
+    /// the application's data directory (see [Backups::for_ini()]).
+    pub fn from_data_dir(ini_path: &Path) -> eyre::Result<Self> {
+        let backups_dir = Settings::project_dirs()?.data_dir().join("backups");
+        Ok(Self::for_ini(&backups_dir, ini_path))
+    }
+
+    /// The backups, newest first.
+    pub fn list(&self) -> eyre::Result<Vec<Backup>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups: Vec<Backup> = std::fs::read_dir(&self.dir)
+            .wrap_err_with(|| eyre::eyre!("Unable to read backups directory {:?}", self.dir))?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let time = backup_time(&path)?;
+                Some(Backup { path, time })
+            })
+            .collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.time));
+        Ok(backups)
+    }
+
+    fn backup_path(&self, time: DateTime<Utc>) -> PathBuf {
+        self.dir.join(format!(
+            "{}{}{}",
+            BACKUP_PREFIX,
+            time.format(BACKUP_TIME_FORMAT),
+            BACKUP_EXTENSION
+        ))
+    }
+
+    /// Copy the file at `ini_path` into a new backup, if it exists,
+    /// and then delete the oldest backups so that no more than
+    /// `max_backups` remain.
+    pub fn create(&self, ini_path: &Path, max_backups: usize) -> eyre::Result<()> {
+        if ini_path.exists() {
+            std::fs::create_dir_all(&self.dir)?;
+            let mut time = DateTime::<Utc>::from(SystemTime::now());
+            let mut backup_path = self.backup_path(time);
+            // Backups created in quick succession are given distinct
+            // times, so that they are not overwritten.
+            while backup_path.exists() {
+                time += chrono::Duration::milliseconds(1);
+                backup_path = self.backup_path(time);
+            }
+            tracing::info!("Backing up {:?} to {:?}", ini_path, backup_path);
+            std::fs::copy(ini_path, &backup_path).wrap_err_with(|| {
+                eyre::eyre!("Unable to back up {:?} to {:?}", ini_path, backup_path)
+            })?;
+        }
+
+        for backup in self.list()?.into_iter().skip(max_backups) {
+            tracing::debug!("Removing old backup {:?}", backup.path);
+            std::fs::remove_file(&backup.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// The time that a backup was created, from its file name.
+fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    let time = path
+        .file_name()?
+        .to_str()?
+        .strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(BACKUP_EXTENSION)?;
+    let time = NaiveDateTime::parse_from_str(time, BACKUP_TIME_FORMAT).ok()?;
+    Some(DateTime::from_naive_utc_and_offset(time, Utc))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{write_atomic, Backups};
+
+    #[test]
+    fn test_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let ini_path = dir.join("scenery_packs.ini");
+        let backups = Backups::new(dir.join("backups"));
+
+        for version in 1..=4 {
+            backups.create(&ini_path, 2).unwrap();
+            write_atomic(&ini_path, format!("version {}", version).as_bytes()).unwrap();
+        }
+
+        assert_eq!("version 4", std::fs::read_to_string(&ini_path).unwrap());
+        assert!(!dir.join("scenery_packs.ini.tmp").exists());
+
+        let contents: Vec<String> = backups
+            .list()
+            .unwrap()
+            .iter()
+            .map(|backup| std::fs::read_to_string(&backup.path).unwrap())
+            .collect();
+        assert_eq!(vec!["version 3", "version 2"], contents);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scenery_packs.ini");
+        std::fs::write(&path, "version 1").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, b"version 2").unwrap();
+
+        assert_eq!("version 2", std::fs::read_to_string(&path).unwrap());
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(0o640, mode & 0o777);
+    }
+
+    #[test]
+    fn test_backups_for_ini() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let backups_dir = dir.join("backups");
+        let xplane_11_ini = dir.join("X-Plane 11").join("scenery_packs.ini");
+        let xplane_12_ini = dir.join("X-Plane 12").join("scenery_packs.ini");
+        for ini_path in [&xplane_11_ini, &xplane_12_ini] {
+            std::fs::create_dir_all(ini_path.parent().unwrap()).unwrap();
+            std::fs::write(ini_path, "version 1").unwrap();
+        }
+
+        Backups::for_ini(&backups_dir, &xplane_11_ini)
+            .create(&xplane_11_ini, 2)
+            .unwrap();
+
+        assert_eq!(
+            1,
+            Backups::for_ini(&backups_dir, &xplane_11_ini)
+                .list()
+                .unwrap()
+                .len()
+        );
+        // Another installation's backups are kept separately.
+        assert!(Backups::for_ini(&backups_dir, &xplane_12_ini)
+            .list()
+            .unwrap()
+            .is_empty());
+        // The same installation reached through a different path.
+        let other_path = dir.join("X-Plane 11/../X-Plane 11/scenery_packs.ini");
+        assert_eq!(
+            1,
+            Backups::for_ini(&backups_dir, &other_path)
+                .list()
+                .unwrap()
+                .len()
+        );
+    }
+}
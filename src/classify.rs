@@ -0,0 +1,375 @@
+//! Classification of scenery packs into categories, based on their
+//! contents.
+
+use std::{collections::BTreeSet, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fl,
+    parsers::scenery_packs::{SceneryPack, SceneryPackPath},
+    scan::{SceneryPackScan, SceneryScan},
+};
+
+/// A category of scenery pack. A scenery pack may belong to more than
+/// one category, e.g. an airport which includes its own library.
+///
+/// Variants are declared in the order that scenery packs of each
+/// category are conventionally loaded.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SceneryCategory {
+    /// Contains an `Earth nav data/apt.dat` file.
+    Airport,
+    /// Contains a `plugins` directory, so some of its scenery may be
+    /// placed by a plugin.
+    PluginDriven,
+    /// Contains overlay DSF tiles (`sim/overlay 1`).
+    Overlay,
+    /// Contains a `library.txt` file.
+    Library,
+    /// Contains base mesh DSF tiles which use the scenery pack's own
+    /// terrain (`.ter`) files, usually orthophotos.
+    Ortho,
+    /// Contains base mesh DSF tiles which use the default terrain.
+    Mesh,
+}
+
+impl SceneryCategory {
+    pub const ALL: [SceneryCategory; 6] = [
+        SceneryCategory::Airport,
+        SceneryCategory::PluginDriven,
+        SceneryCategory::Overlay,
+        SceneryCategory::Library,
+        SceneryCategory::Ortho,
+        SceneryCategory::Mesh,
+    ];
+
+    /// Localized name of the category.
+    pub fn label(&self) -> String {
+        match self {
+            SceneryCategory::Airport => fl!("category-airport"),
+            SceneryCategory::PluginDriven => fl!("category-plugin-driven"),
+            SceneryCategory::Overlay => fl!("category-overlay"),
+            SceneryCategory::Library => fl!("category-library"),
+            SceneryCategory::Ortho => fl!("category-ortho"),
+            SceneryCategory::Mesh => fl!("category-mesh"),
+        }
+    }
+}
+
+/// Determine the categories of the scenery pack located in
+/// `pack_dir`, using the files in the directory and the information
+/// already gathered in `scan`.
+pub fn classify(pack_dir: &Path, scan: &SceneryPackScan) -> BTreeSet<SceneryCategory> {
+    let mut categories = BTreeSet::new();
+
+    if scan.library.is_some() {
+        categories.insert(SceneryCategory::Library);
+    }
+
+    if pack_dir.join("Earth nav data").join("apt.dat").is_file() {
+        categories.insert(SceneryCategory::Airport);
+    }
+
+    if pack_dir.join("plugins").is_dir() {
+        categories.insert(SceneryCategory::PluginDriven);
+    }
+
+    for tile in &scan.tiles {
+        let category = if tile.overlay {
+            SceneryCategory::Overlay
+        } else if tile.local_terrain {
+            SceneryCategory::Ortho
+        } else {
+            SceneryCategory::Mesh
+        };
+        categories.insert(category);
+    }
+
+    categories
+}
+
+/// The position in `scenery_packs` at which to insert a new scenery
+/// pack belonging to `categories`, so that it is loaded in the
+/// conventional order: directly before the first scenery pack whose
+/// primary (earliest) category is loaded later. Scenery packs with
+/// unknown categories are skipped over. If the categories of the new
+/// scenery pack are unknown, it is inserted at the top, as X-Plane
+/// itself does.
+pub fn insert_position(
+    scenery_packs: &im_rc::Vector<SceneryPack>,
+    scan: &SceneryScan,
+    categories: &BTreeSet<SceneryCategory>,
+) -> usize {
+    let category = match categories.iter().next() {
+        Some(category) => category,
+        None => return 0,
+    };
+
+    scenery_packs
+        .iter()
+        .position(|scenery_pack| {
+            scan.packs
+                .get(&scenery_pack.path)
+                .and_then(|pack_scan| pack_scan.categories.iter().next())
+                .map(|pack_category| pack_category > category)
+                .unwrap_or(false)
+        })
+        .unwrap_or(scenery_packs.len())
+}
+
+/// A rule for sorting the load order of scenery packs. See
+/// [sort_scenery_packs()].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortRule {
+    /// Scenery packs belonging to the category.
+    Category(SceneryCategory),
+    /// The `*GLOBAL_AIRPORTS*` entry.
+    GlobalAirports,
+}
+
+impl SortRule {
+    /// The conventional load order: custom airports, global airports,
+    /// plugin-driven scenery, overlays, libraries, ortho, then mesh.
+    pub const DEFAULT: [SortRule; 7] = [
+        SortRule::Category(SceneryCategory::Airport),
+        SortRule::GlobalAirports,
+        SortRule::Category(SceneryCategory::PluginDriven),
+        SortRule::Category(SceneryCategory::Overlay),
+        SortRule::Category(SceneryCategory::Library),
+        SortRule::Category(SceneryCategory::Ortho),
+        SortRule::Category(SceneryCategory::Mesh),
+    ];
+
+    /// Localized name of the rule.
+    pub fn label(&self) -> String {
+        match self {
+            SortRule::Category(category) => category.label(),
+            SortRule::GlobalAirports => fl!("sort-rule-global-airports"),
+        }
+    }
+
+    fn matches(&self, scenery_pack: &SceneryPack, scan: &SceneryScan) -> bool {
+        let is_global_airports = scenery_pack.location() == SceneryPackPath::GlobalAirports;
+        match self {
+            // The global airports are only sorted by their own rule,
+            // even though they are scanned as an airport.
+            SortRule::Category(_) if is_global_airports => false,
+            SortRule::Category(category) => scan
+                .packs
+                .get(&scenery_pack.path)
+                .map(|pack_scan| pack_scan.categories.contains(category))
+                .unwrap_or(false),
+            SortRule::GlobalAirports => is_global_airports,
+        }
+    }
+}
+
+/// Sort `scenery_packs` into the load order given by `rules`. Each
+/// scenery pack is placed according to the first rule which it matches,
+/// so a scenery pack in several categories is sorted by whichever of
+/// them comes first in `rules`. Scenery packs which match no rule
+/// (e.g. those which have not been scanned) stay with the scenery pack
+/// which they currently follow. The sort is stable, so scenery packs
+/// which match the same rule keep their current relative order.
+pub fn sort_scenery_packs(
+    scenery_packs: &im_rc::Vector<SceneryPack>,
+    scan: &SceneryScan,
+    rules: &[SortRule],
+) -> im_rc::Vector<SceneryPack> {
+    let mut rank = 0;
+    let mut ranked: Vec<(usize, &SceneryPack)> = scenery_packs
+        .iter()
+        .map(|scenery_pack| {
+            if let Some(rule_rank) = rules
+                .iter()
+                .position(|rule| rule.matches(scenery_pack, scan))
+            {
+                rank = rule_rank;
+            }
+            (rank, scenery_pack)
+        })
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked
+        .into_iter()
+        .map(|(_, scenery_pack)| scenery_pack.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::{classify, insert_position, sort_scenery_packs, SceneryCategory, SortRule};
+    use crate::{
+        parsers::scenery_packs::SceneryPack,
+        scan::{SceneryPackScan, SceneryScan, TileScan},
+    };
+
+    fn tile(overlay: bool, local_terrain: bool) -> TileScan {
+        TileScan {
+            overlay,
+            local_terrain,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pack_dir = temp_dir.path().join("NZQN");
+        std::fs::create_dir_all(pack_dir.join("Earth nav data")).unwrap();
+        std::fs::create_dir_all(pack_dir.join("plugins")).unwrap();
+        std::fs::write(pack_dir.join("Earth nav data").join("apt.dat"), "").unwrap();
+
+        let scan = SceneryPackScan {
+            tiles: vec![tile(true, false), tile(false, true)],
+            ..Default::default()
+        };
+        let categories = classify(&pack_dir, &scan);
+        std::fs::remove_dir_all(&pack_dir).unwrap();
+
+        let expected: BTreeSet<SceneryCategory> = [
+            SceneryCategory::Airport,
+            SceneryCategory::PluginDriven,
+            SceneryCategory::Overlay,
+            SceneryCategory::Ortho,
+        ]
+        .iter()
+        .copied()
+        .collect();
+        assert_eq!(expected, categories);
+
+        let scan = SceneryPackScan {
+            tiles: vec![tile(false, false)],
+            ..Default::default()
+        };
+        let expected: BTreeSet<SceneryCategory> = [SceneryCategory::Mesh].iter().copied().collect();
+        assert_eq!(expected, classify(&pack_dir, &scan));
+    }
+
+    #[test]
+    fn test_insert_position() {
+        let mut scan = SceneryScan::default();
+        let mut add_pack = |path: &str, categories: &[SceneryCategory]| {
+            scan.packs.insert(
+                path.into(),
+                SceneryPackScan {
+                    categories: categories.iter().copied().collect(),
+                    ..Default::default()
+                },
+            );
+            SceneryPack::new(path, true)
+        };
+        let scenery_packs: im_rc::Vector<SceneryPack> = vec![
+            add_pack(
+                "Custom Scenery/NZQN/",
+                &[SceneryCategory::Airport, SceneryCategory::Library],
+            ),
+            add_pack("Custom Scenery/Unknown/", &[]),
+            add_pack("Custom Scenery/Library/", &[SceneryCategory::Library]),
+            add_pack("Custom Scenery/Ortho/", &[SceneryCategory::Ortho]),
+        ]
+        .into();
+
+        let categories = |categories: &[SceneryCategory]| -> BTreeSet<SceneryCategory> {
+            categories.iter().copied().collect()
+        };
+        assert_eq!(
+            2,
+            insert_position(
+                &scenery_packs,
+                &scan,
+                &categories(&[SceneryCategory::Airport])
+            )
+        );
+        assert_eq!(
+            3,
+            insert_position(
+                &scenery_packs,
+                &scan,
+                &categories(&[SceneryCategory::Library])
+            )
+        );
+        assert_eq!(
+            4,
+            insert_position(&scenery_packs, &scan, &categories(&[SceneryCategory::Mesh]))
+        );
+        assert_eq!(0, insert_position(&scenery_packs, &scan, &categories(&[])));
+    }
+
+    #[test]
+    fn test_sort_scenery_packs() {
+        let mut scan = SceneryScan::default();
+        let mut add_pack = |path: &str, categories: &[SceneryCategory]| {
+            scan.packs.insert(
+                path.into(),
+                SceneryPackScan {
+                    categories: categories.iter().copied().collect(),
+                    ..Default::default()
+                },
+            );
+            SceneryPack::new(path, true)
+        };
+        let scenery_packs: im_rc::Vector<SceneryPack> = vec![
+            add_pack("Custom Scenery/Mesh/", &[SceneryCategory::Mesh]),
+            add_pack("Custom Scenery/Unknown/", &[]),
+            add_pack("Custom Scenery/Library/", &[SceneryCategory::Library]),
+            add_pack("*GLOBAL_AIRPORTS*", &[]),
+            add_pack(
+                "Custom Scenery/NZQN/",
+                &[SceneryCategory::Airport, SceneryCategory::Library],
+            ),
+            add_pack("Custom Scenery/Overlay/", &[SceneryCategory::Overlay]),
+            add_pack("Custom Scenery/EDDF/", &[SceneryCategory::Airport]),
+        ]
+        .into();
+
+        let paths = |scenery_packs: &im_rc::Vector<SceneryPack>| -> Vec<String> {
+            scenery_packs
+                .iter()
+                .map(|scenery_pack| scenery_pack.path.to_string_lossy().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            vec![
+                "Custom Scenery/NZQN/",
+                "Custom Scenery/EDDF/",
+                "*GLOBAL_AIRPORTS*",
+                "Custom Scenery/Overlay/",
+                "Custom Scenery/Library/",
+                "Custom Scenery/Mesh/",
+                "Custom Scenery/Unknown/",
+            ],
+            paths(&sort_scenery_packs(
+                &scenery_packs,
+                &scan,
+                &SortRule::DEFAULT
+            ))
+        );
+
+        // Libraries before airports, and scenery packs matching no rule
+        // stay after the scenery pack they follow.
+        let rules = [
+            SortRule::Category(SceneryCategory::Library),
+            SortRule::Category(SceneryCategory::Airport),
+            SortRule::Category(SceneryCategory::Mesh),
+        ];
+        assert_eq!(
+            vec![
+                "Custom Scenery/Library/",
+                "*GLOBAL_AIRPORTS*",
+                "Custom Scenery/NZQN/",
+                "Custom Scenery/Overlay/",
+                "Custom Scenery/EDDF/",
+                "Custom Scenery/Mesh/",
+                "Custom Scenery/Unknown/",
+            ],
+            paths(&sort_scenery_packs(&scenery_packs, &scan, &rules))
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Dependencies between scenery packs and the libraries which provide
+//! the definitions that they reference.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{parsers::scenery_packs::SceneryPack, scan::SceneryScan};
+
+/// Virtual paths, grouped by the scenery pack which requires or
+/// provides them.
+pub type PathsByPack = BTreeMap<PathBuf, BTreeSet<String>>;
+
+/// A graph of which library scenery packs each scenery pack requires,
+/// calculated from a [SceneryScan]. Scenery packs are identified by
+/// [SceneryPack::path](crate::parsers::scenery_packs::SceneryPack::path).
+#[derive(Clone, Debug, Default)]
+pub struct DependencyGraph {
+    /// For each scenery pack, the libraries it requires and the virtual
+    /// paths it uses from them.
+    requires: HashMap<PathBuf, PathsByPack>,
+    /// For each library, the scenery packs which require it and the
+    /// virtual paths they use from it.
+    required_by: HashMap<PathBuf, PathsByPack>,
+    /// For each scenery pack, the virtual paths which are not provided
+    /// by any library.
+    unresolved: HashMap<PathBuf, BTreeSet<String>>,
+}
+
+impl DependencyGraph {
+    pub fn new(scan: &SceneryScan) -> Self {
+        let mut providers: HashMap<String, BTreeSet<PathBuf>> = HashMap::new();
+        for (pack_path, pack_scan) in &scan.packs {
+            if let Some(library) = &pack_scan.library {
+                for export in &library.exports {
+                    providers
+                        .entry(export.virtual_path.clone())
+                        .or_default()
+                        .insert(pack_path.clone());
+                }
+            }
+        }
+
+        let mut requires: HashMap<PathBuf, PathsByPack> = HashMap::new();
+        let mut required_by: HashMap<PathBuf, PathsByPack> = HashMap::new();
+        let mut unresolved: HashMap<PathBuf, BTreeSet<String>> = HashMap::new();
+
+        for (pack_path, pack_scan) in &scan.packs {
+            for reference in &pack_scan.external_references {
+                let reference_providers = providers.get(reference);
+                let provided_by_self = reference_providers
+                    .map(|libraries| libraries.contains(pack_path))
+                    .unwrap_or(false);
+
+                if provided_by_self {
+                    continue;
+                }
+
+                match reference_providers {
+                    Some(libraries) => {
+                        for library in libraries {
+                            requires
+                                .entry(pack_path.clone())
+                                .or_default()
+                                .entry(library.clone())
+                                .or_default()
+                                .insert(reference.clone());
+                            required_by
+                                .entry(library.clone())
+                                .or_default()
+                                .entry(pack_path.clone())
+                                .or_default()
+                                .insert(reference.clone());
+                        }
+                    }
+                    None => {
+                        if !scan.default_exports.contains(reference) {
+                            unresolved
+                                .entry(pack_path.clone())
+                                .or_default()
+                                .insert(reference.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            requires,
+            required_by,
+            unresolved,
+        }
+    }
+
+    /// The libraries required by the specified scenery pack, and the
+    /// virtual paths it uses from each of them. Where more than one
+    /// library exports a virtual path, all of them are included.
+    pub fn requires(&self, pack: &Path) -> Option<&PathsByPack> {
+        self.requires.get(pack)
+    }
+
+    /// The scenery packs which require the specified library, and the
+    /// virtual paths each of them uses from it.
+    pub fn required_by(&self, library: &Path) -> Option<&PathsByPack> {
+        self.required_by.get(library)
+    }
+
+    /// Virtual paths referenced by the specified scenery pack which are
+    /// not provided by any library (including X-Plane's default
+    /// libraries).
+    pub fn unresolved(&self, pack: &Path) -> Option<&BTreeSet<String>> {
+        self.unresolved.get(pack)
+    }
+
+    /// Enable or disable the scenery pack at `index` in
+    /// `scenery_packs`, along with the libraries affected by the
+    /// change:
+    ///
+    /// + When enabling, every virtual path required by the scenery
+    ///   pack which is not already provided by an enabled library is
+    ///   provided by enabling the library highest in the load order
+    ///   which exports it. This applies recursively to the libraries
+    ///   which are enabled.
+    /// + When disabling, every library required by the scenery pack
+    ///   which is no longer required by any enabled scenery pack is
+    ///   disabled. This also applies recursively.
+    ///
+    /// Libraries which were not required by the scenery pack are left
+    /// untouched, because the references of some scenery packs may not
+    /// be known.
+    pub fn set_enabled(
+        &self,
+        scenery_packs: &im_rc::Vector<SceneryPack>,
+        index: usize,
+        enabled: bool,
+    ) -> im_rc::Vector<SceneryPack> {
+        let mut indices: HashMap<&Path, usize> = HashMap::new();
+        for (pack_index, scenery_pack) in scenery_packs.iter().enumerate() {
+            indices
+                .entry(scenery_pack.path.as_path())
+                .or_insert(pack_index);
+        }
+        let mut scenery_packs = scenery_packs.clone();
+
+        let path = match scenery_packs.get_mut(index) {
+            Some(scenery_pack) => {
+                scenery_pack.enabled = enabled;
+                scenery_pack.path.clone()
+            }
+            None => {
+                tracing::error!("No scenery pack exists for the index {}", index);
+                return scenery_packs;
+            }
+        };
+
+        let mut queue: Vec<PathBuf> = vec![path];
+        while let Some(path) = queue.pop() {
+            let requires = match self.requires(&path) {
+                Some(requires) => requires,
+                None => continue,
+            };
+
+            if enabled {
+                let mut providers_by_path: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+                for (library, virtual_paths) in requires {
+                    if let Some(library_index) = indices.get(library.as_path()) {
+                        for virtual_path in virtual_paths {
+                            providers_by_path
+                                .entry(virtual_path)
+                                .or_default()
+                                .push(*library_index);
+                        }
+                    }
+                }
+
+                for library_indices in providers_by_path.values() {
+                    let provided = library_indices
+                        .iter()
+                        .any(|library_index| scenery_packs[*library_index].enabled);
+                    if provided {
+                        continue;
+                    }
+                    if let Some(library_index) = library_indices.iter().min() {
+                        let library = &mut scenery_packs[*library_index];
+                        library.enabled = true;
+                        queue.push(library.path.clone());
+                    }
+                }
+            } else {
+                for library in requires.keys() {
+                    let library_index = match indices.get(library.as_path()) {
+                        Some(library_index) if scenery_packs[*library_index].enabled => {
+                            *library_index
+                        }
+                        _ => continue,
+                    };
+
+                    let still_required = self
+                        .required_by(library)
+                        .map(|dependents| {
+                            dependents.keys().any(|dependent| {
+                                indices
+                                    .get(dependent.as_path())
+                                    .map(|dependent_index| scenery_packs[*dependent_index].enabled)
+                                    .unwrap_or(false)
+                            })
+                        })
+                        .unwrap_or(false);
+
+                    if !still_required {
+                        scenery_packs[library_index].enabled = false;
+                        queue.push(library.clone());
+                    }
+                }
+            }
+        }
+
+        scenery_packs
+    }
+
+    /// Enable or disable several scenery packs at once, as specified
+    /// by `changes` (pairs of index and enabled state). The libraries
+    /// affected are enabled and disabled according to the same rules
+    /// as [DependencyGraph::set_enabled()]. Scenery packs are disabled
+    /// before any are enabled, so that a library which is required by
+    /// a newly enabled scenery pack is never left disabled.
+    pub fn set_enabled_many(
+        &self,
+        scenery_packs: &im_rc::Vector<SceneryPack>,
+        changes: &[(usize, bool)],
+    ) -> im_rc::Vector<SceneryPack> {
+        let disable = changes.iter().filter(|(_, enabled)| !enabled);
+        let enable = changes.iter().filter(|(_, enabled)| *enabled);
+
+        disable
+            .chain(enable)
+            .fold(scenery_packs.clone(), |scenery_packs, (index, enabled)| {
+                self.set_enabled(&scenery_packs, *index, *enabled)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use pretty_assertions::assert_eq;
+
+    use super::DependencyGraph;
+    use crate::{
+        parsers::{
+            library::{ExportKind, LibraryExport, LibraryTxt, Visibility},
+            scenery_packs::SceneryPack,
+        },
+        scan::{SceneryPackScan, SceneryScan},
+    };
+
+    fn library(virtual_paths: &[&str]) -> LibraryTxt {
+        LibraryTxt {
+            version: 800,
+            regions: Vec::new(),
+            exports: virtual_paths
+                .iter()
+                .map(|virtual_path| LibraryExport {
+                    kind: ExportKind::Export,
+                    virtual_path: virtual_path.to_string(),
+                    real_path: PathBuf::from("objects/a.obj"),
+                    region: None,
+                    visibility: Visibility::Public,
+                })
+                .collect(),
+        }
+    }
+
+    fn references(paths: &[&str]) -> SceneryPackScan {
+        SceneryPackScan {
+            external_references: paths.iter().map(|path| path.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn example_scan() -> SceneryScan {
+        let mut scan = SceneryScan::default();
+        scan.packs.insert(
+            "Custom Scenery/NZQN/".into(),
+            references(&[
+                "lib/people/walker.obj",
+                "lib/vehicles/tug.obj",
+                "lib/missing.obj",
+            ]),
+        );
+        scan.packs.insert(
+            "Custom Scenery/NZCH/".into(),
+            references(&["lib/vehicles/tug.obj", "lib/g10/roads.net"]),
+        );
+        scan.packs.insert(
+            "Custom Scenery/People Library/".into(),
+            SceneryPackScan {
+                library: Some(library(&["lib/people/walker.obj"])),
+                ..Default::default()
+            },
+        );
+        scan.packs.insert(
+            "Custom Scenery/Vehicle Library/".into(),
+            SceneryPackScan {
+                library: Some(library(&["lib/vehicles/tug.obj"])),
+                ..Default::default()
+            },
+        );
+        scan.default_exports.insert("lib/g10/roads.net".to_string());
+        scan
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let graph = DependencyGraph::new(&example_scan());
+
+        insta::assert_debug_snapshot!(graph.requires(Path::new("Custom Scenery/NZQN/")));
+        insta::assert_debug_snapshot!(
+            graph.required_by(Path::new("Custom Scenery/Vehicle Library/"))
+        );
+
+        assert_eq!(
+            vec!["lib/missing.obj"],
+            graph
+                .unresolved(Path::new("Custom Scenery/NZQN/"))
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(None, graph.unresolved(Path::new("Custom Scenery/NZCH/")));
+    }
+
+    fn example_scenery_packs(enabled: &[bool]) -> im_rc::Vector<SceneryPack> {
+        [
+            "Custom Scenery/NZQN/",
+            "Custom Scenery/NZCH/",
+            "Custom Scenery/People Library/",
+            "Custom Scenery/Vehicle Library/",
+        ]
+        .iter()
+        .zip(enabled)
+        .map(|(path, enabled)| SceneryPack::new(path, *enabled))
+        .collect()
+    }
+
+    fn enabled(scenery_packs: &im_rc::Vector<SceneryPack>) -> Vec<bool> {
+        scenery_packs
+            .iter()
+            .map(|scenery_pack| scenery_pack.enabled)
+            .collect()
+    }
+
+    #[test]
+    fn test_set_enabled() {
+        let graph = DependencyGraph::new(&example_scan());
+
+        // Enabling NZQN enables both of the libraries it requires.
+        let scenery_packs = example_scenery_packs(&[false, false, false, false]);
+        let scenery_packs = graph.set_enabled(&scenery_packs, 0, true);
+        assert_eq!(vec![true, false, true, true], enabled(&scenery_packs));
+
+        // Enabling NZCH requires nothing new.
+        let scenery_packs = graph.set_enabled(&scenery_packs, 1, true);
+        assert_eq!(vec![true, true, true, true], enabled(&scenery_packs));
+
+        // Disabling NZQN disables the people library, but the vehicle
+        // library is still required by NZCH.
+        let scenery_packs = graph.set_enabled(&scenery_packs, 0, false);
+        assert_eq!(vec![false, true, false, true], enabled(&scenery_packs));
+
+        // Disabling NZCH disables the vehicle library.
+        let scenery_packs = graph.set_enabled(&scenery_packs, 1, false);
+        assert_eq!(vec![false, false, false, false], enabled(&scenery_packs));
+    }
+
+    #[test]
+    fn test_set_enabled_many() {
+        let graph = DependencyGraph::new(&example_scan());
+
+        // Swapping NZQN for NZCH disables the people library, but the
+        // vehicle library is still required.
+        let scenery_packs = example_scenery_packs(&[true, false, true, true]);
+        let scenery_packs = graph.set_enabled_many(&scenery_packs, &[(1, true), (0, false)]);
+        assert_eq!(vec![false, true, false, true], enabled(&scenery_packs));
+    }
+}
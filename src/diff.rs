@@ -0,0 +1,194 @@
+//! Differences between two states of the scenery packs, usually the
+//! state saved in `scenery_packs.ini` and the current state in memory.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::parsers::scenery_packs::SceneryPack;
+
+/// How a single scenery pack has changed.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SceneryPackChange {
+    pub path: PathBuf,
+    /// The position of the scenery pack in the saved state, or `None`
+    /// if it has been added.
+    pub saved_index: Option<usize>,
+    /// The position of the scenery pack in the current state, or
+    /// `None` if it has been removed.
+    pub index: Option<usize>,
+    /// The new enabled state of the scenery pack, if it has been
+    /// enabled or disabled.
+    pub enabled: Option<bool>,
+    /// Whether the scenery pack has moved relative to the other
+    /// scenery packs. Scenery packs which have only shifted because
+    /// others were added, removed or moved around them are not
+    /// considered to have moved.
+    pub moved: bool,
+}
+
+impl SceneryPackChange {
+    pub fn is_added(&self) -> bool {
+        self.saved_index.is_none()
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.index.is_none()
+    }
+}
+
+/// The changed scenery packs, in their current order, followed by the
+/// removed scenery packs in their saved order.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct SceneryPacksDiff {
+    pub changes: Vec<SceneryPackChange>,
+}
+
+impl SceneryPacksDiff {
+    pub fn new(saved: &im_rc::Vector<SceneryPack>, current: &im_rc::Vector<SceneryPack>) -> Self {
+        let saved_indices: HashMap<&PathBuf, usize> = saved
+            .iter()
+            .enumerate()
+            .map(|(index, scenery_pack)| (&scenery_pack.path, index))
+            .collect();
+        let current_paths: HashSet<&PathBuf> = current
+            .iter()
+            .map(|scenery_pack| &scenery_pack.path)
+            .collect();
+
+        // The scenery packs which have not moved are the largest set
+        // whose order is unchanged.
+        let common_saved_indices: Vec<usize> = current
+            .iter()
+            .filter_map(|scenery_pack| saved_indices.get(&scenery_pack.path).copied())
+            .collect();
+        let unmoved = longest_increasing_subsequence(&common_saved_indices);
+
+        let mut changes: Vec<SceneryPackChange> = current
+            .iter()
+            .enumerate()
+            .filter_map(|(index, scenery_pack)| {
+                let saved_index = saved_indices.get(&scenery_pack.path).copied();
+                let enabled = match saved_index {
+                    Some(saved_index) if saved[saved_index].enabled == scenery_pack.enabled => None,
+                    _ => Some(scenery_pack.enabled),
+                };
+                let moved = saved_index
+                    .map(|saved_index| !unmoved.contains(&saved_index))
+                    .unwrap_or(false);
+
+                if saved_index.is_some() && enabled.is_none() && !moved {
+                    return None;
+                }
+                Some(SceneryPackChange {
+                    path: scenery_pack.path.clone(),
+                    saved_index,
+                    index: Some(index),
+                    enabled,
+                    moved,
+                })
+            })
+            .collect();
+
+        changes.extend(
+            saved
+                .iter()
+                .enumerate()
+                .filter(|(_, scenery_pack)| !current_paths.contains(&scenery_pack.path))
+                .map(|(saved_index, scenery_pack)| SceneryPackChange {
+                    path: scenery_pack.path.clone(),
+                    saved_index: Some(saved_index),
+                    index: None,
+                    enabled: None,
+                    moved: false,
+                }),
+        );
+
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The changes to scenery packs which are in the current state,
+    /// by path.
+    pub fn by_path(&self) -> HashMap<&Path, &SceneryPackChange> {
+        self.changes
+            .iter()
+            .filter(|change| !change.is_removed())
+            .map(|change| (change.path.as_path(), change))
+            .collect()
+    }
+}
+
+/// The values of the longest strictly increasing subsequence of
+/// `values`.
+fn longest_increasing_subsequence(values: &[usize]) -> HashSet<usize> {
+    // `tails[length - 1]` is the position in `values` of the smallest
+    // value which ends an increasing subsequence of `length`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = Vec::with_capacity(values.len());
+
+    for (position, value) in values.iter().enumerate() {
+        let length = tails.partition_point(|tail| values[*tail] < *value);
+        predecessors.push(length.checked_sub(1).map(|previous| tails[previous]));
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+
+    let mut subsequence = HashSet::new();
+    let mut position = tails.last().copied();
+    while let Some(current) = position {
+        subsequence.insert(values[current]);
+        position = predecessors[current];
+    }
+    subsequence
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{longest_increasing_subsequence, SceneryPacksDiff};
+    use crate::parsers::scenery_packs::SceneryPack;
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        let mut subsequence: Vec<usize> = longest_increasing_subsequence(&[0, 4, 1, 2, 5, 3])
+            .into_iter()
+            .collect();
+        subsequence.sort_unstable();
+        assert_eq!(vec![0, 1, 2, 3], subsequence);
+        assert!(longest_increasing_subsequence(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let saved: im_rc::Vector<SceneryPack> = vec![
+            SceneryPack::new("Custom Scenery/NZQN/", true),
+            SceneryPack::new("Custom Scenery/Removed/", true),
+            SceneryPack::new("Custom Scenery/NZCH/", true),
+            SceneryPack::new("Custom Scenery/Library/", false),
+            SceneryPack::new("Custom Scenery/Mesh/", true),
+        ]
+        .into();
+        let current: im_rc::Vector<SceneryPack> = vec![
+            SceneryPack::new("Custom Scenery/Added/", true),
+            SceneryPack::new("Custom Scenery/NZQN/", true),
+            SceneryPack::new("Custom Scenery/Mesh/", true),
+            SceneryPack::new("Custom Scenery/NZCH/", false),
+            SceneryPack::new("Custom Scenery/Library/", false),
+        ]
+        .into();
+
+        insta::assert_json_snapshot!(SceneryPacksDiff::new(&saved, &current));
+        assert!(SceneryPacksDiff::new(&saved, &saved).is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+//! Geographic areas, used to enable only the scenery packs which cover
+//! a particular part of the world.
+
+use serde::{Deserialize, Serialize};
+
+/// Mean radius of the earth in nautical miles.
+const EARTH_RADIUS_NM: f64 = 3440.065;
+
+/// A position on the earth, in decimal degrees.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct LatLon {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl LatLon {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    /// Great circle distance to `other` in nautical miles.
+    pub fn distance_nm(&self, other: &LatLon) -> f64 {
+        let lat1 = self.lat.to_radians();
+        let lat2 = other.lat.to_radians();
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_NM * a.sqrt().asin()
+    }
+
+    /// The point `fraction` of the way along the great circle from
+    /// this point to `other`.
+    pub fn interpolate(&self, other: &LatLon, fraction: f64) -> LatLon {
+        let angular_distance = self.distance_nm(other) / EARTH_RADIUS_NM;
+        if angular_distance == 0.0 {
+            return *self;
+        }
+
+        let lat1 = self.lat.to_radians();
+        let lon1 = self.lon.to_radians();
+        let lat2 = other.lat.to_radians();
+        let lon2 = other.lon.to_radians();
+
+        let a = ((1.0 - fraction) * angular_distance).sin() / angular_distance.sin();
+        let b = (fraction * angular_distance).sin() / angular_distance.sin();
+        let x = a * lat1.cos() * lon1.cos() + b * lat2.cos() * lon2.cos();
+        let y = a * lat1.cos() * lon1.sin() + b * lat2.cos() * lon2.sin();
+        let z = a * lat1.sin() + b * lat2.sin();
+
+        LatLon::new(
+            z.atan2((x * x + y * y).sqrt()).to_degrees(),
+            y.atan2(x).to_degrees(),
+        )
+    }
+}
+
+/// Maximum distance in nautical miles between the points sampled
+/// along a route when checking whether a [GeoArea::Corridor]
+/// intersects a tile or point.
+const CORRIDOR_SAMPLE_NM: f64 = 5.0;
+
+/// Points along the great circle segments of `route`, no more than
+/// [CORRIDOR_SAMPLE_NM] apart.
+fn sample_route(route: &[LatLon]) -> impl Iterator<Item = LatLon> + '_ {
+    let segments = route.windows(2).flat_map(|segment| {
+        let (start, end) = (segment[0], segment[1]);
+        let steps = (start.distance_nm(&end) / CORRIDOR_SAMPLE_NM)
+            .ceil()
+            .max(1.0) as usize;
+        (0..steps).map(move |step| start.interpolate(&end, step as f64 / steps as f64))
+    });
+    segments.chain(route.last().copied())
+}
+
+/// Normalize a longitude difference into the range `-180..180`.
+fn normalize_lon_delta(delta: f64) -> f64 {
+    let delta = (delta + 180.0).rem_euclid(360.0) - 180.0;
+    if delta == -180.0 {
+        180.0
+    } else {
+        delta
+    }
+}
+
+/// A one degree by one degree DSF tile, identified by its south-west
+/// corner.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tile {
+    pub south: i32,
+    pub west: i32,
+}
+
+impl Tile {
+    /// Parse the tile from a DSF file name, e.g. `+45-170.dsf`.
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let stem = file_name.split('.').next()?;
+        let split = stem.get(1..)?.find(['+', '-'])? + 1;
+        let south = stem[..split].parse().ok()?;
+        let west = stem[split..].parse().ok()?;
+        Some(Self { south, west })
+    }
+
+    /// The point within this tile which is nearest to `point`. This is
+    /// calculated by clamping the latitude and longitude, which is
+    /// not exact near the poles, but close enough at the scale of a
+    /// single tile.
+    fn nearest_point(&self, point: &LatLon) -> LatLon {
+        let lat = point.lat.clamp(self.south as f64, (self.south + 1) as f64);
+
+        let west = self.west as f64;
+        let delta = normalize_lon_delta(point.lon - west);
+        let lon = if (0.0..=1.0).contains(&delta) {
+            point.lon
+        } else if delta < 0.0 {
+            west
+        } else {
+            west + 1.0
+        };
+
+        LatLon::new(lat, lon)
+    }
+}
+
+/// The width in degrees of the longitudes from `west` eastwards to
+/// `east`, which is all of them if `east` is at least 360° beyond
+/// `west`.
+fn lon_width(west: f64, east: f64) -> f64 {
+    if east - west >= 360.0 {
+        360.0
+    } else {
+        (east - west).rem_euclid(360.0)
+    }
+}
+
+/// An area of the earth which scenery packs may intersect.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GeoArea {
+    /// A box bounded by lines of latitude and longitude. If `west` is
+    /// greater than `east` the box crosses the antimeridian, and if
+    /// `east - west` is at least 360 it covers all longitudes.
+    BoundingBox {
+        south: f64,
+        west: f64,
+        north: f64,
+        east: f64,
+    },
+    /// All points within `radius_nm` nautical miles of `centre`.
+    Circle { centre: LatLon, radius_nm: f64 },
+    /// All points within `width_nm / 2` nautical miles either side of
+    /// a route made up of great circle segments between waypoints.
+    Corridor { route: Vec<LatLon>, width_nm: f64 },
+}
+
+impl GeoArea {
+    /// Whether the area contains the specified point.
+    pub fn contains(&self, point: &LatLon) -> bool {
+        match self {
+            GeoArea::BoundingBox {
+                south,
+                west,
+                north,
+                east,
+            } => {
+                let width = lon_width(*west, *east);
+                let offset = (point.lon - west).rem_euclid(360.0);
+                (*south..=*north).contains(&point.lat) && offset <= width
+            }
+            GeoArea::Circle { centre, radius_nm } => centre.distance_nm(point) <= *radius_nm,
+            GeoArea::Corridor { route, width_nm } => {
+                let radius_nm = width_nm / 2.0 + CORRIDOR_SAMPLE_NM / 2.0;
+                sample_route(route).any(|sample| sample.distance_nm(point) <= radius_nm)
+            }
+        }
+    }
+
+    /// Whether the area intersects the specified DSF tile.
+    pub fn intersects_tile(&self, tile: &Tile) -> bool {
+        match self {
+            GeoArea::BoundingBox {
+                south,
+                west,
+                north,
+                east,
+            } => {
+                let tile_south = tile.south as f64;
+                let lat_overlaps = tile_south < *north && tile_south + 1.0 > *south;
+
+                let width = lon_width(*west, *east);
+                let tile_offset = (tile.west as f64 - west).rem_euclid(360.0);
+                // Either the tile's west edge lies within the box, or
+                // the box's west edge lies within the tile.
+                let lon_overlaps = tile_offset < width || tile_offset > 359.0;
+
+                lat_overlaps && lon_overlaps
+            }
+            GeoArea::Circle { centre, radius_nm } => {
+                centre.distance_nm(&tile.nearest_point(centre)) <= *radius_nm
+            }
+            GeoArea::Corridor { route, width_nm } => {
+                let radius_nm = width_nm / 2.0 + CORRIDOR_SAMPLE_NM / 2.0;
+                sample_route(route)
+                    .any(|sample| sample.distance_nm(&tile.nearest_point(&sample)) <= radius_nm)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{GeoArea, LatLon, Tile};
+
+    #[test]
+    fn test_tile_from_file_name() {
+        assert_eq!(
+            Some(Tile {
+                south: 45,
+                west: -170
+            }),
+            Tile::from_file_name("+45-170.dsf")
+        );
+        assert_eq!(
+            Some(Tile {
+                south: -45,
+                west: 168
+            }),
+            Tile::from_file_name("-45+168.dsf")
+        );
+        assert_eq!(None, Tile::from_file_name("overlay.dsf"));
+    }
+
+    #[test]
+    fn test_circle() {
+        // NZQN, Queenstown
+        let area = GeoArea::Circle {
+            centre: LatLon::new(-45.0211, 168.7392),
+            radius_nm: 150.0,
+        };
+        // NZCH, Christchurch, is roughly 190nm away.
+        assert!(!area.contains(&LatLon::new(-43.4894, 172.5322)));
+        // NZDN, Dunedin, is roughly 80nm away.
+        assert!(area.contains(&LatLon::new(-45.9281, 170.1983)));
+
+        assert!(area.intersects_tile(&Tile {
+            south: -46,
+            west: 168
+        }));
+        assert!(area.intersects_tile(&Tile {
+            south: -44,
+            west: 170
+        }));
+        assert!(!area.intersects_tile(&Tile {
+            south: -43,
+            west: 173
+        }));
+        assert!(!area.intersects_tile(&Tile {
+            south: -46,
+            west: -170
+        }));
+    }
+
+    #[test]
+    fn test_corridor() {
+        // NZQN to NZCH
+        let area = GeoArea::Corridor {
+            route: vec![
+                LatLon::new(-45.0211, 168.7392),
+                LatLon::new(-43.4894, 172.5322),
+            ],
+            width_nm: 20.0,
+        };
+        // Near the middle of the route.
+        assert!(area.contains(&LatLon::new(-44.3, 170.5)));
+        // NZDN, Dunedin, is not.
+        assert!(!area.contains(&LatLon::new(-45.9281, 170.1983)));
+
+        assert!(area.intersects_tile(&Tile {
+            south: -45,
+            west: 170
+        }));
+        assert!(!area.intersects_tile(&Tile {
+            south: -46,
+            west: 170
+        }));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let area = GeoArea::BoundingBox {
+            south: -47.5,
+            west: 165.5,
+            north: -34.0,
+            east: -178.0,
+        };
+        assert!(area.contains(&LatLon::new(-45.0, 179.5)));
+        assert!(area.contains(&LatLon::new(-45.0, -179.5)));
+        assert!(!area.contains(&LatLon::new(-45.0, 160.0)));
+
+        assert!(area.intersects_tile(&Tile {
+            south: -48,
+            west: 166
+        }));
+        assert!(area.intersects_tile(&Tile {
+            south: -40,
+            west: -179
+        }));
+        assert!(area.intersects_tile(&Tile {
+            south: -40,
+            west: 165
+        }));
+        assert!(!area.intersects_tile(&Tile {
+            south: -40,
+            west: 164
+        }));
+        assert!(!area.intersects_tile(&Tile {
+            south: -34,
+            west: 170
+        }));
+    }
+
+    #[test]
+    fn test_bounding_box_all_longitudes() {
+        let area = GeoArea::BoundingBox {
+            south: -10.0,
+            west: -180.0,
+            north: 10.0,
+            east: 180.0,
+        };
+        assert!(area.contains(&LatLon::new(0.0, 0.0)));
+        assert!(area.contains(&LatLon::new(0.0, 170.0)));
+        assert!(area.contains(&LatLon::new(0.0, -179.5)));
+        assert!(!area.contains(&LatLon::new(20.0, 0.0)));
+
+        assert!(area.intersects_tile(&Tile { south: 0, west: 0 }));
+        assert!(area.intersects_tile(&Tile {
+            south: -1,
+            west: -180
+        }));
+        assert!(area.intersects_tile(&Tile {
+            south: 5,
+            west: 179
+        }));
+        assert!(!area.intersects_tile(&Tile { south: 20, west: 0 }));
+    }
+}
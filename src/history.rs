@@ -23,6 +23,7 @@ impl<T: Default> Default for History<T> {
 }
 
 impl<T: Clone> History<T> {
+    #[cfg(test)]
     pub fn new(initial_state: T) -> Self {
         Self {
             stack: vec![initial_state],
@@ -35,10 +36,6 @@ impl<T: Clone> History<T> {
         self.pointer = 0;
     }
 
-    pub fn position(&self) -> usize {
-        self.pointer
-    }
-
     pub fn peek_prev(&self) -> Option<(&T, usize)> {
         if self.pointer == 0 {
             return None;
@@ -131,6 +128,7 @@ impl<T: Clone> History<T> {
         Some((item, self.pointer))
     }
 
+    #[cfg(test)]
     pub fn len(&self) -> usize {
         self.stack.len()
     }
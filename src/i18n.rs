@@ -34,6 +34,7 @@ pub struct I18nGuard<'r> {
 pub fn setup_i18n<'r>() -> eyre::Result<I18nGuard<'r>> {
     let localizer = DefaultLocalizer::new(&*LANGUAGE_LOADER, &Localizations);
     let mut language_requester = DesktopLanguageRequester::new();
+    #[allow(clippy::arc_with_non_send_sync)]
     let localizer_arc: Arc<dyn Localizer> = Arc::new(localizer);
     language_requester.add_listener(Arc::downgrade(&localizer_arc));
 
@@ -1,17 +1,28 @@
+mod airports;
 mod app;
+mod backup;
+mod classify;
+mod dependencies;
+mod diff;
+mod geo;
 mod history;
 mod i18n;
+mod merge;
 mod parsers;
+mod presets;
+mod route;
+mod scan;
+mod selection;
 mod settings;
 mod state;
+mod tags;
 mod view;
+mod xplane;
 
 use app::ScenableApp;
 use tracing_log::LogTracer;
 use tracing_subscriber::EnvFilter;
 
-use std::convert::TryFrom;
-
 use crate::i18n::setup_i18n;
 
 /// Set up logging and error reporting
@@ -21,7 +32,7 @@ fn setup_reporting() {
         .expect("Unable to setup tracing-log");
 
     let env_filter: EnvFilter = match std::env::var("RUST_LOG") {
-        Ok(env) => match EnvFilter::try_from(env) {
+        Ok(env) => match EnvFilter::try_new(env) {
             Ok(filter) => Some(filter),
             Err(err) => {
                 eprintln!("Unable to parse `RUST_LOG` envirnoment variable: {}.", err);
@@ -30,7 +41,7 @@ fn setup_reporting() {
         },
         Err(_) => None,
     }
-    .unwrap_or_else(|| EnvFilter::try_from("info").unwrap());
+    .unwrap_or_else(|| EnvFilter::new("info"));
 
     let subscriber = tracing_subscriber::fmt()
         .with_env_filter(env_filter)
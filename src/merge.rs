@@ -0,0 +1,230 @@
+//! Three-way merging of scenery packs, used when `scenery_packs.ini` is
+//! modified by another program (such as X-Plane itself) while there
+//! are unsaved changes in memory.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::parsers::scenery_packs::SceneryPack;
+
+/// The result of [merge()].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeResult {
+    pub scenery_packs: im_rc::Vector<SceneryPack>,
+    /// Scenery packs which were removed on one side and enabled or
+    /// disabled on the other. These were resolved in favour of `ours`.
+    pub conflicts: Vec<PathBuf>,
+    /// Whether both sides reordered the scenery packs. This was
+    /// resolved using the order of `ours`.
+    pub order_conflict: bool,
+}
+
+/// Merge the changes made in `ours` and `theirs` since they diverged
+/// from `base`.
+///
+/// + Scenery packs added on either side are kept, and scenery packs
+///   removed on either side are removed.
+/// + A scenery pack which was enabled or disabled on one side takes
+///   that state.
+/// + If only one side reordered the scenery packs, its order is used.
+///   Scenery packs added on the other side are placed after the same
+///   scenery pack that they followed on that side.
+pub fn merge(
+    base: &im_rc::Vector<SceneryPack>,
+    ours: &im_rc::Vector<SceneryPack>,
+    theirs: &im_rc::Vector<SceneryPack>,
+) -> MergeResult {
+    let base_enabled = enabled_by_path(base);
+    let ours_enabled = enabled_by_path(ours);
+    let theirs_enabled = enabled_by_path(theirs);
+
+    let mut conflicts: Vec<PathBuf> = Vec::new();
+    let mut kept: HashSet<&PathBuf> = HashSet::new();
+    let all_paths = ours
+        .iter()
+        .chain(theirs)
+        .map(|scenery_pack| &scenery_pack.path);
+    for path in all_paths {
+        if kept.contains(path) || conflicts.contains(path) {
+            continue;
+        }
+        let keep = match (
+            base_enabled.get(path),
+            ours_enabled.get(path),
+            theirs_enabled.get(path),
+        ) {
+            // Added on one or both sides.
+            (None, _, _) => true,
+            (Some(_), Some(_), Some(_)) => true,
+            // Removed on one side, which conflicts with enabling or
+            // disabling it on the other side.
+            (Some(base), Some(ours), None) => {
+                let changed = ours != base;
+                if changed {
+                    conflicts.push(path.clone());
+                }
+                changed
+            }
+            (Some(base), None, Some(theirs)) => {
+                if theirs != base {
+                    conflicts.push(path.clone());
+                }
+                false
+            }
+            (Some(_), None, None) => false,
+        };
+        if keep {
+            kept.insert(path);
+        }
+    }
+
+    let ours_reordered = is_reordered(base, ours);
+    let theirs_reordered = is_reordered(base, theirs);
+    let (primary, secondary) = if ours_reordered {
+        (ours, theirs)
+    } else {
+        (theirs, ours)
+    };
+
+    let mut paths: Vec<PathBuf> = primary
+        .iter()
+        .map(|scenery_pack| &scenery_pack.path)
+        .filter(|path| kept.contains(path))
+        .cloned()
+        .collect();
+
+    // Insert the scenery packs which are only on the secondary side
+    // after the scenery pack which they follow there.
+    let mut previous: Option<&PathBuf> = None;
+    for scenery_pack in secondary {
+        let path = &scenery_pack.path;
+        if kept.contains(path) && !paths.contains(path) {
+            let index = previous
+                .and_then(|previous| paths.iter().position(|other| other == previous))
+                .map(|index| index + 1)
+                .unwrap_or(0);
+            paths.insert(index, path.clone());
+        }
+        if paths.contains(path) {
+            previous = Some(path);
+        }
+    }
+
+    let scenery_packs = paths
+        .into_iter()
+        .map(|path| {
+            let ours = ours_enabled.get(&path).copied();
+            let theirs = theirs_enabled.get(&path).copied();
+            let enabled = match (base_enabled.get(&path).copied(), ours, theirs) {
+                (Some(base), Some(ours), Some(theirs)) if ours == base => theirs,
+                (_, Some(ours), _) => ours,
+                (_, None, Some(theirs)) => theirs,
+                (_, None, None) => unreachable!("merged scenery pack exists on neither side"),
+            };
+            SceneryPack { enabled, path }
+        })
+        .collect();
+
+    MergeResult {
+        scenery_packs,
+        conflicts,
+        order_conflict: ours_reordered && theirs_reordered,
+    }
+}
+
+fn enabled_by_path(scenery_packs: &im_rc::Vector<SceneryPack>) -> HashMap<PathBuf, bool> {
+    scenery_packs
+        .iter()
+        .map(|scenery_pack| (scenery_pack.path.clone(), scenery_pack.enabled))
+        .collect()
+}
+
+/// Whether the scenery packs which `side` has in common with `base`
+/// are in a different order.
+fn is_reordered(base: &im_rc::Vector<SceneryPack>, side: &im_rc::Vector<SceneryPack>) -> bool {
+    let base_paths: HashSet<&PathBuf> = base.iter().map(|pack| &pack.path).collect();
+    let side_paths: HashSet<&PathBuf> = side.iter().map(|pack| &pack.path).collect();
+    let base_order = base
+        .iter()
+        .map(|pack| &pack.path)
+        .filter(|path| side_paths.contains(path));
+    let side_order = side
+        .iter()
+        .map(|pack| &pack.path)
+        .filter(|path| base_paths.contains(path));
+    !base_order.eq(side_order)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::merge;
+    use crate::parsers::scenery_packs::SceneryPack;
+
+    #[test]
+    fn test_merge() {
+        let base = im_rc::vector![
+            SceneryPack::new("A", true),
+            SceneryPack::new("B", true),
+            SceneryPack::new("C", true),
+            SceneryPack::new("D", true),
+        ];
+        // Disabled B, moved D to the top, removed C.
+        let ours = im_rc::vector![
+            SceneryPack::new("D", true),
+            SceneryPack::new("A", true),
+            SceneryPack::new("B", false),
+        ];
+        // X-Plane added a new pack N after A, and disabled D.
+        let theirs = im_rc::vector![
+            SceneryPack::new("A", true),
+            SceneryPack::new("N", true),
+            SceneryPack::new("B", true),
+            SceneryPack::new("C", true),
+            SceneryPack::new("D", false),
+        ];
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            im_rc::vector![
+                SceneryPack::new("D", false),
+                SceneryPack::new("A", true),
+                SceneryPack::new("N", true),
+                SceneryPack::new("B", false),
+            ],
+            result.scenery_packs
+        );
+        assert!(result.conflicts.is_empty());
+        assert!(!result.order_conflict);
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let base = im_rc::vector![
+            SceneryPack::new("A", true),
+            SceneryPack::new("B", true),
+            SceneryPack::new("C", true),
+        ];
+        let ours = im_rc::vector![
+            SceneryPack::new("B", true),
+            SceneryPack::new("A", false),
+            SceneryPack::new("C", true),
+        ];
+        let theirs = im_rc::vector![SceneryPack::new("C", true), SceneryPack::new("B", true)];
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            im_rc::vector![
+                SceneryPack::new("B", true),
+                SceneryPack::new("A", false),
+                SceneryPack::new("C", true),
+            ],
+            result.scenery_packs
+        );
+        assert_eq!(vec![std::path::PathBuf::from("A")], result.conflicts);
+        assert!(result.order_conflict);
+    }
+}
@@ -0,0 +1,377 @@
+//! Parser for X-Plane `apt.dat` airport files. Only the airport
+//! headers, runways, helipads and metadata are read; taxiways,
+//! frequencies and so on are skipped. The files are read a line at a
+//! time, as the global airports' `apt.dat` is hundreds of megabytes.
+
+use std::{io::BufRead, path::Path, str::FromStr};
+
+use eyre::Context;
+use nom::{
+    bytes::complete::is_not,
+    character::complete::{not_line_ending, space0, space1},
+    combinator::{map_res, opt},
+    multi::count,
+    sequence::preceded,
+    IResult,
+};
+use serde::Serialize;
+
+use crate::geo::LatLon;
+
+/// The type of an airport, from the row code of its header.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AirportKind {
+    /// A land airport (row code `1`).
+    Airport,
+    /// A seaplane base (row code `16`).
+    SeaplaneBase,
+    /// A heliport (row code `17`).
+    Heliport,
+}
+
+/// A runway (row code `100`), water runway (`101`) or helipad (`102`).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Runway {
+    /// The designators of the runway's ends, e.g. `["05", "23"]`, or
+    /// of the helipad, e.g. `["H1"]`.
+    pub idents: Vec<String>,
+    /// The locations of the runway's ends, or of the helipad.
+    pub ends: Vec<LatLon>,
+}
+
+/// An airport defined in an `apt.dat` file.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Airport {
+    pub kind: AirportKind,
+    /// The airport's identifier, usually its ICAO code if it has one.
+    pub ident: String,
+    pub name: String,
+    /// Elevation in feet above mean sea level.
+    pub elevation: i32,
+    pub runways: Vec<Runway>,
+    /// The `datum_lat` and `datum_lon` metadata (row code `1302`).
+    pub datum: Option<LatLon>,
+    /// The `icao_code` metadata, for airports whose identifier is not
+    /// their ICAO code.
+    pub icao_code: Option<String>,
+    /// The `region_code` metadata, e.g. `NZ`.
+    pub region: Option<String>,
+}
+
+impl Airport {
+    /// The location of the airport: its datum if present, otherwise
+    /// the centre of its runways and helipads.
+    pub fn location(&self) -> Option<LatLon> {
+        if self.datum.is_some() {
+            return self.datum;
+        }
+        let ends: Vec<&LatLon> = self
+            .runways
+            .iter()
+            .flat_map(|runway| &runway.ends)
+            .collect();
+        if ends.is_empty() {
+            return None;
+        }
+        let count = ends.len() as f64;
+        Some(LatLon::new(
+            ends.iter().map(|end| end.lat).sum::<f64>() / count,
+            ends.iter().map(|end| end.lon).sum::<f64>() / count,
+        ))
+    }
+
+    /// The airport's ICAO code if it has one, otherwise its identifier.
+    pub fn icao(&self) -> &str {
+        self.icao_code.as_deref().unwrap_or(&self.ident)
+    }
+}
+
+/// A row of an `apt.dat` file which is of interest.
+#[derive(Debug, PartialEq)]
+enum Row<'a> {
+    Airport {
+        kind: AirportKind,
+        elevation: i32,
+        ident: &'a str,
+        name: &'a str,
+    },
+    Runway(Runway),
+    Metadata {
+        key: &'a str,
+        value: &'a str,
+    },
+    /// The end of the file (row code `99`).
+    End,
+}
+
+/// A whitespace separated field.
+fn field(input: &str) -> IResult<&str, &str> {
+    preceded(space0, is_not(" \t\r\n"))(input)
+}
+
+fn number<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(field, str::parse::<T>)(input)
+}
+
+/// Skip `n` fields.
+fn skip(n: usize) -> impl FnMut(&str) -> IResult<&str, ()> {
+    move |input| {
+        let (input, _) = count(field, n)(input)?;
+        Ok((input, ()))
+    }
+}
+
+/// Parses the designator and location of a runway end or helipad.
+fn runway_end(input: &str) -> IResult<&str, (String, LatLon)> {
+    let (input, ident) = field(input)?;
+    let (input, lat) = number(input)?;
+    let (input, lon) = number(input)?;
+    Ok((input, (ident.to_string(), LatLon::new(lat, lon))))
+}
+
+fn runway(ends: Vec<(String, LatLon)>) -> Row<'static> {
+    let (idents, ends) = ends.into_iter().unzip();
+    Row::Runway(Runway { idents, ends })
+}
+
+/// Parses an airport header, e.g. `1 1171 0 0 NZQN Queenstown`.
+fn airport_header(kind: AirportKind) -> impl FnMut(&str) -> IResult<&str, Row> {
+    move |input| {
+        let (input, elevation) = number(input)?;
+        let (input, _) = skip(2)(input)?;
+        let (input, ident) = field(input)?;
+        let (input, name) = opt(preceded(space1, not_line_ending))(input)?;
+        let row = Row::Airport {
+            kind,
+            elevation,
+            ident,
+            name: name.unwrap_or_default().trim(),
+        };
+        Ok((input, row))
+    }
+}
+
+/// Parses a land runway, e.g. `100 45.00 1 0 0.25 1 3 0 05 -45.02540000
+/// 168.72577000 0 0 2 0 0 0 23 -45.01699000 168.75218000 0 0 2 0 0 0`.
+fn land_runway(input: &str) -> IResult<&str, Row<'_>> {
+    let (input, _) = skip(7)(input)?;
+    let (input, end1) = runway_end(input)?;
+    let (input, _) = skip(6)(input)?;
+    let (input, end2) = runway_end(input)?;
+    Ok((input, runway(vec![end1, end2])))
+}
+
+/// Parses a water runway, e.g. `101 49 1 08 35.04 -106.59 26 35.04
+/// -106.61`.
+fn water_runway(input: &str) -> IResult<&str, Row<'_>> {
+    let (input, _) = skip(2)(input)?;
+    let (input, end1) = runway_end(input)?;
+    let (input, end2) = runway_end(input)?;
+    Ok((input, runway(vec![end1, end2])))
+}
+
+/// Parses a helipad, e.g. `102 H1 -45.0 168.0 0.00 10.00 10.00 1 0 0
+/// 0.25 0`.
+fn helipad(input: &str) -> IResult<&str, Row<'_>> {
+    let (input, end) = runway_end(input)?;
+    Ok((input, runway(vec![end])))
+}
+
+/// Parses a metadata row, e.g. `1302 datum_lat -45.021111`.
+fn metadata(input: &str) -> IResult<&str, Row<'_>> {
+    let (input, key) = field(input)?;
+    let (input, value) = opt(preceded(space1, not_line_ending))(input)?;
+    let row = Row::Metadata {
+        key,
+        value: value.unwrap_or_default().trim(),
+    };
+    Ok((input, row))
+}
+
+/// Parses a row of an `apt.dat` file, returning `None` for rows which
+/// are not of interest.
+fn row(input: &str) -> IResult<&str, Option<Row<'_>>> {
+    let (rest, row_code) = field(input)?;
+    let (rest, row) = match row_code {
+        "1" => airport_header(AirportKind::Airport)(rest)?,
+        "16" => airport_header(AirportKind::SeaplaneBase)(rest)?,
+        "17" => airport_header(AirportKind::Heliport)(rest)?,
+        "100" => land_runway(rest)?,
+        "101" => water_runway(rest)?,
+        "102" => helipad(rest)?,
+        "1302" => metadata(rest)?,
+        "99" => (rest, Row::End),
+        _ => return Ok((input, None)),
+    };
+    Ok((rest, Some(row)))
+}
+
+/// Read the airports from an `apt.dat` file. Rows which cannot be
+/// parsed are skipped.
+pub fn apt_dat(mut reader: impl BufRead) -> std::io::Result<Vec<Airport>> {
+    let mut airports: Vec<Airport> = Vec::new();
+    // The `datum_lat` and `datum_lon` of the current airport, which only
+    // become its datum once both have been read.
+    let mut datum_lat: Option<f64> = None;
+    let mut datum_lon: Option<f64> = None;
+    let mut line_bytes = Vec::new();
+    loop {
+        line_bytes.clear();
+        if reader.read_until(b'\n', &mut line_bytes)? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&line_bytes);
+        let row = match row(&line) {
+            Ok((_, Some(row))) => row,
+            _ => continue,
+        };
+
+        match row {
+            Row::Airport {
+                kind,
+                elevation,
+                ident,
+                name,
+            } => {
+                datum_lat = None;
+                datum_lon = None;
+                airports.push(Airport {
+                    kind,
+                    ident: ident.to_string(),
+                    name: name.to_string(),
+                    elevation,
+                    runways: Vec::new(),
+                    datum: None,
+                    icao_code: None,
+                    region: None,
+                })
+            }
+            Row::End => break,
+            row => {
+                // Rows before the first airport header are ignored.
+                let airport = match airports.last_mut() {
+                    Some(airport) => airport,
+                    None => continue,
+                };
+                match row {
+                    Row::Runway(runway) => airport.runways.push(runway),
+                    Row::Metadata { key, value } => match key {
+                        "datum_lat" | "datum_lon" => {
+                            let value = match value.parse::<f64>() {
+                                Ok(value) => value,
+                                Err(_) => continue,
+                            };
+                            if key == "datum_lat" {
+                                datum_lat = Some(value);
+                            } else {
+                                datum_lon = Some(value);
+                            }
+                            if let (Some(lat), Some(lon)) = (datum_lat, datum_lon) {
+                                airport.datum = Some(LatLon::new(lat, lon));
+                            }
+                        }
+                        "icao_code" => airport.icao_code = Some(value.to_string()),
+                        "region_code" => airport.region = Some(value.to_string()),
+                        _ => {}
+                    },
+                    Row::Airport { .. } | Row::End => unreachable!(),
+                }
+            }
+        }
+    }
+
+    Ok(airports)
+}
+
+/// Read the airports from the `apt.dat` file at `path`.
+pub fn read_apt_dat(path: impl AsRef<Path>) -> eyre::Result<Vec<Airport>> {
+    let path = path.as_ref();
+    let file =
+        std::fs::File::open(path).wrap_err_with(|| eyre::eyre!("Unable to open {:?}", path))?;
+    apt_dat(std::io::BufReader::new(file))
+        .wrap_err_with(|| eyre::eyre!("Unable to read {:?}", path))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{apt_dat, row, AirportKind, Row};
+    use crate::geo::LatLon;
+
+    #[test]
+    fn test_row() {
+        assert_eq!(
+            Some(Row::Airport {
+                kind: AirportKind::Airport,
+                elevation: 1171,
+                ident: "NZQN",
+                name: "Queenstown",
+            }),
+            row("1    1171 0 0 NZQN Queenstown\r\n").unwrap().1
+        );
+        assert_eq!(
+            Some(Row::Metadata {
+                key: "region_code",
+                value: "NZ",
+            }),
+            row("1302 region_code NZ\n").unwrap().1
+        );
+        assert_eq!(None, row("110 2 0.25 0.00 Taxiway").unwrap().1);
+        assert!(row("100 45.00 1 0").is_err());
+    }
+
+    #[test]
+    fn test_apt_dat() {
+        let apt_dat_file = "I\n\
+            1100 Version - data cycle 2013.10\n\
+            \n\
+            1    1171 0 0 NZQN Queenstown\n\
+            1302 datum_lat -45.021111\n\
+            1302 datum_lon 168.739167\n\
+            1302 icao_code NZQN\n\
+            1302 region_code NZ\n\
+            100 45.00 1 0 0.25 1 3 0 05 -45.02540000 168.72577000 0 0 2 0 0 0 23 -45.01699000 168.75218000 0 0 2 0 0 0\n\
+            110 2 0.25 0.00 Taxiway\n\
+            \n\
+            16   0 0 0 X01 Lake Seaplane Base\n\
+            101 49 1 08 -45.00000000 168.00000000 26 -45.00000000 168.02000000\n\
+            \n\
+            17   0 0 0 H001 Hospital Helipad\n\
+            102 H1 -45.00000000 168.00000000 0.00 10.00 10.00 1 0 0 0.25 0\n\
+            99\n";
+        let airports = apt_dat(apt_dat_file.as_bytes()).unwrap();
+        insta::assert_json_snapshot!(airports);
+
+        let locations: Vec<Option<LatLon>> =
+            airports.iter().map(|airport| airport.location()).collect();
+        assert_eq!(
+            vec![
+                Some(LatLon::new(-45.021111, 168.739167)),
+                Some(LatLon::new(-45.0, 168.01)),
+                Some(LatLon::new(-45.0, 168.0)),
+            ],
+            locations
+        );
+    }
+
+    #[test]
+    fn test_apt_dat_partial_datum() {
+        let apt_dat_file = "I\n\
+            1100 Version - data cycle 2013.10\n\
+            \n\
+            17   0 0 0 H001 Hospital Helipad\n\
+            1302 datum_lat -45.500000\n\
+            102 H1 -45.00000000 168.00000000 0.00 10.00 10.00 1 0 0 0.25 0\n\
+            \n\
+            17   0 0 0 H002 Another Helipad\n\
+            1302 datum_lon 170.000000\n\
+            102 H1 -46.00000000 169.00000000 0.00 10.00 10.00 1 0 0 0.25 0\n\
+            99\n";
+        let airports = apt_dat(apt_dat_file.as_bytes()).unwrap();
+        assert_eq!(None, airports[0].datum);
+        assert_eq!(None, airports[1].datum);
+        assert_eq!(Some(LatLon::new(-45.0, 168.0)), airports[0].location());
+        assert_eq!(Some(LatLon::new(-46.0, 169.0)), airports[1].location());
+    }
+}
@@ -0,0 +1,140 @@
+//! Parser for the references in X-Plane art asset files (`.agp`,
+//! `.fac`, `.for`, `.lin`, `.pol` and `.obj`) to the other files they
+//! use, which may be virtual paths exported by a library.
+
+use std::{io::BufRead, path::Path};
+
+use eyre::Context;
+use nom::{
+    bytes::complete::is_not,
+    character::complete::{not_line_ending, space0, space1},
+    combinator::verify,
+    sequence::preceded,
+    IResult,
+};
+
+/// The file extensions of the art asset files which are read by
+/// [read_art_asset_references()].
+pub const ART_ASSET_EXTENSIONS: &[&str] = &["agp", "fac", "for", "lin", "pol", "obj"];
+
+/// Keywords which are followed by the path of a file used by the art
+/// asset.
+const REFERENCE_KEYWORDS: &[&str] = &[
+    "TEXTURE",
+    "TEXTURE_LIT",
+    "TEXTURE_NORMAL",
+    "TEXTURE_NOWRAP",
+    "OBJECT",
+    "FACADE",
+    "OBJ",
+];
+
+/// The keyword which starts the geometry of an `.obj` file, after
+/// which there are no more references.
+const GEOMETRY_KEYWORD: &str = "POINT_COUNTS";
+
+fn keyword(input: &str) -> IResult<&str, &str> {
+    preceded(space0, is_not(" \t\r\n"))(input)
+}
+
+/// Parses a line of an art asset file, e.g. `TEXTURE ../textures/a.dds`,
+/// returning the path if it is a reference.
+fn reference(input: &str) -> IResult<&str, &str> {
+    let (input, _) = verify(keyword, |keyword: &str| {
+        REFERENCE_KEYWORDS.contains(&keyword)
+    })(input)?;
+    let (input, path) = preceded(
+        space1,
+        verify(not_line_ending, |path: &str| !path.trim().is_empty()),
+    )(input)?;
+    Ok((input, path.trim()))
+}
+
+/// Read the paths of the files referenced by an art asset file, as
+/// they are written in the file (relative to the art asset file, or
+/// virtual paths exported by a library).
+pub fn art_asset_references(mut reader: impl BufRead) -> std::io::Result<Vec<String>> {
+    let mut references = Vec::new();
+    let mut line_bytes = Vec::new();
+    loop {
+        line_bytes.clear();
+        if reader.read_until(b'\n', &mut line_bytes)? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&line_bytes);
+        if let Ok((_, keyword)) = keyword(&line) {
+            if keyword == GEOMETRY_KEYWORD {
+                break;
+            }
+        }
+        if let Ok((_, path)) = reference(&line) {
+            references.push(path.to_string());
+        }
+    }
+    Ok(references)
+}
+
+/// Read the paths of the files referenced by the art asset file at
+/// `path`.
+pub fn read_art_asset_references(path: impl AsRef<Path>) -> eyre::Result<Vec<String>> {
+    let path = path.as_ref();
+    let file =
+        std::fs::File::open(path).wrap_err_with(|| eyre::eyre!("Unable to open {:?}", path))?;
+    art_asset_references(std::io::BufReader::new(file))
+        .wrap_err_with(|| eyre::eyre!("Unable to read {:?}", path))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{art_asset_references, reference};
+
+    #[test]
+    fn test_reference() {
+        assert_eq!(
+            "lib/people/walker.obj",
+            reference("OBJECT lib/people/walker.obj\r\n").unwrap().1
+        );
+        assert_eq!(
+            "../textures/wall.dds",
+            reference("\tTEXTURE  ../textures/wall.dds").unwrap().1
+        );
+        assert!(reference("OBJ\n").is_err());
+        assert!(reference("TEXTURE_SCALE 256 256").is_err());
+    }
+
+    #[test]
+    fn test_art_asset_references() {
+        let obj_file = "I\n\
+            800\n\
+            OBJ\n\
+            \n\
+            TEXTURE hangar.png\n\
+            TEXTURE_LIT hangar_LIT.png\n\
+            POINT_COUNTS 4 0 0 6\n\
+            TEXTURE not_a_reference.png\n";
+        assert_eq!(
+            vec!["hangar.png", "hangar_LIT.png"],
+            art_asset_references(obj_file.as_bytes()).unwrap()
+        );
+
+        let agp_file = "A\n\
+            1000\n\
+            AG_POINT\n\
+            \n\
+            TEXTURE ground.dds\n\
+            TEXTURE_SCALE 256 256\n\
+            OBJECT lib/airport/vehicles/baggage.obj\n\
+            FACADE lib/airport/fences/fence.fac\n\
+            OBJ_DRAPED 0 0 0 0\n";
+        assert_eq!(
+            vec![
+                "ground.dds",
+                "lib/airport/vehicles/baggage.obj",
+                "lib/airport/fences/fence.fac",
+            ],
+            art_asset_references(agp_file.as_bytes()).unwrap()
+        );
+    }
+}
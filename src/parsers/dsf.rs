@@ -0,0 +1,748 @@
+//! A reader for the subset of the [DSF file
+//! format](https://developer.x-plane.com/article/dsf-file-format-specification/)
+//! required to determine a tile's properties and which scenery
+//! definitions it references. Geometry (`GEOD`), elevation (`DEMS`) and
+//! command (`CMDS`) atoms are located but their contents are not
+//! decoded.
+//!
+//! DSF files compressed with 7-Zip (as most shipped tiles are) are
+//! decompressed in memory before they are parsed.
+
+use std::{
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use nom::{
+    bytes::complete::take,
+    number::complete::{le_i32, le_u32},
+    IResult,
+};
+use serde::{Serialize, Serializer};
+use sevenz_rust::{Password, SevenZReader};
+
+/// The cookie at the start of every DSF file.
+const COOKIE: &[u8; 8] = b"XPLNEDSF";
+/// The signature at the start of a 7-Zip archive.
+const SEVEN_ZIP_SIGNATURE: &[u8; 6] = &[b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+/// The only version of the DSF master header that is currently
+/// specified.
+const SUPPORTED_VERSION: i32 = 1;
+/// Size of the cookie and version at the start of the file.
+const HEADER_SIZE: usize = 12;
+/// Size of the id and size at the start of every atom.
+const ATOM_HEADER_SIZE: usize = 8;
+/// Size of the MD5 hash at the end of the file.
+const FOOTER_SIZE: usize = 16;
+
+/// Identifies the type of an atom. Stored in the file as a little
+/// endian 32 bit integer, so `HEAD` appears in the file as `DAEH`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AtomId(pub [u8; 4]);
+
+impl AtomId {
+    pub const HEAD: AtomId = AtomId(*b"HEAD");
+    pub const PROP: AtomId = AtomId(*b"PROP");
+    pub const DEFN: AtomId = AtomId(*b"DEFN");
+    pub const TERT: AtomId = AtomId(*b"TERT");
+    pub const OBJT: AtomId = AtomId(*b"OBJT");
+    pub const POLY: AtomId = AtomId(*b"POLY");
+    pub const NETW: AtomId = AtomId(*b"NETW");
+    pub const DEMN: AtomId = AtomId(*b"DEMN");
+    pub const GEOD: AtomId = AtomId(*b"GEOD");
+    pub const DEMS: AtomId = AtomId(*b"DEMS");
+
+    fn from_file_value(value: u32) -> Self {
+        Self(value.to_be_bytes())
+    }
+
+    /// Whether this atom contains other atoms rather than data.
+    fn is_container(&self) -> bool {
+        [Self::HEAD, Self::DEFN, Self::GEOD, Self::DEMS].contains(self)
+    }
+}
+
+impl std::fmt::Display for AtomId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from_utf8_lossy(&self.0).fmt(f)
+    }
+}
+
+impl std::fmt::Debug for AtomId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AtomId({})", self)
+    }
+}
+
+impl Serialize for AtomId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// An atom in the DSF file's atom tree.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Atom {
+    pub id: AtomId,
+    /// Byte offset of the start of this atom (including its header)
+    /// from the start of the file.
+    pub offset: usize,
+    /// Size of this atom in bytes, including its header.
+    pub size: usize,
+    /// Child atoms, if this is a container atom (`HEAD`, `DEFN`,
+    /// `GEOD` or `DEMS`).
+    pub children: Vec<Atom>,
+}
+
+/// An error encountered while reading a DSF file.
+#[derive(Debug)]
+pub enum DsfError {
+    Io(std::io::Error),
+    /// Error while decompressing a 7-Zip compressed DSF file.
+    Decompression(sevenz_rust::Error),
+    /// A 7-Zip compressed DSF file does not contain any files.
+    EmptyArchive,
+    /// The file ended before the data at `offset` could be read.
+    Truncated {
+        offset: usize,
+        needed: usize,
+        available: usize,
+    },
+    /// The file does not start with `XPLNEDSF`.
+    InvalidCookie {
+        found: Vec<u8>,
+    },
+    UnsupportedVersion {
+        offset: usize,
+        version: i32,
+    },
+    /// An atom's size is too small to contain its header, or extends
+    /// beyond the end of its parent.
+    InvalidAtomSize {
+        offset: usize,
+        id: AtomId,
+        size: usize,
+    },
+    /// A required atom was not present in the file.
+    MissingAtom {
+        id: AtomId,
+    },
+    /// The `PROP` atom contains a property name without a value.
+    UnpairedProperty {
+        offset: usize,
+        name: String,
+    },
+    /// A string table's final string is not null terminated.
+    UnterminatedString {
+        offset: usize,
+    },
+    /// The MD5 hash at the end of the file does not match its
+    /// contents.
+    ChecksumMismatch {
+        offset: usize,
+        expected: [u8; 16],
+        actual: [u8; 16],
+    },
+}
+
+impl std::fmt::Display for DsfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DsfError::Io(error) => write!(f, "Error reading DSF file: {}", error),
+            DsfError::Decompression(error) => {
+                write!(f, "Error decompressing DSF file: {}", error)
+            }
+            DsfError::EmptyArchive => write!(f, "Compressed DSF file contains no files"),
+            DsfError::Truncated {
+                offset,
+                needed,
+                available,
+            } => write!(
+                f,
+                "DSF file is truncated at byte {}: {} bytes needed but only {} available",
+                offset, needed, available
+            ),
+            DsfError::InvalidCookie { found } => write!(
+                f,
+                "Not a DSF file, expected cookie \"XPLNEDSF\" but found {:?}",
+                String::from_utf8_lossy(found)
+            ),
+            DsfError::UnsupportedVersion { offset, version } => {
+                write!(f, "Unsupported DSF version {} at byte {}", version, offset)
+            }
+            DsfError::InvalidAtomSize { offset, id, size } => write!(
+                f,
+                "Atom {} at byte {} has an invalid size of {} bytes",
+                id, offset, size
+            ),
+            DsfError::MissingAtom { id } => write!(f, "DSF file has no {} atom", id),
+            DsfError::UnpairedProperty { offset, name } => write!(
+                f,
+                "Property {:?} at byte {} does not have a value",
+                name, offset
+            ),
+            DsfError::UnterminatedString { offset } => {
+                write!(f, "String at byte {} is not null terminated", offset)
+            }
+            DsfError::ChecksumMismatch {
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "DSF MD5 checksum at byte {} does not match: expected {}, calculated {}",
+                offset,
+                hex(expected),
+                hex(actual)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DsfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DsfError::Io(error) => Some(error),
+            DsfError::Decompression(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DsfError {
+    fn from(error: std::io::Error) -> Self {
+        DsfError::Io(error)
+    }
+}
+
+impl From<sevenz_rust::Error> for DsfError {
+    fn from(error: sevenz_rust::Error) -> Self {
+        DsfError::Decompression(error)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The contents of a DSF file which are relevant to Scenable.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DsfFile {
+    pub version: i32,
+    /// The top level atoms in the file.
+    pub atoms: Vec<Atom>,
+    /// Name/value pairs from the `PROP` atom, in the order they appear
+    /// in the file. Names may be repeated (e.g.
+    /// `sim/require_object`).
+    pub properties: Vec<(String, String)>,
+    /// Terrain definitions (`TERT`), e.g. `lib/g10/terrain10/fst.ter`.
+    pub terrain_definitions: Vec<String>,
+    /// Object definitions (`OBJT`).
+    pub object_definitions: Vec<String>,
+    /// Polygon definitions (`POLY`), e.g. facades, forests and
+    /// draped polygons.
+    pub polygon_definitions: Vec<String>,
+    /// Network definitions (`NETW`), e.g. `lib/g10/roads.net`.
+    pub network_definitions: Vec<String>,
+    /// Raster names (`DEMN`) for the elevation data in `DEMS`.
+    pub raster_definitions: Vec<String>,
+}
+
+impl DsfFile {
+    /// Read a DSF file which may be compressed with 7-Zip.
+    pub fn read<R: Read + Seek>(mut reader: R) -> Result<Self, DsfError> {
+        let mut signature = [0u8; SEVEN_ZIP_SIGNATURE.len()];
+        // A file shorter than the signature cannot be a 7-Zip archive.
+        let is_seven_zip = match reader.read_exact(&mut signature) {
+            Ok(()) => &signature == SEVEN_ZIP_SIGNATURE,
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => false,
+            Err(error) => return Err(error.into()),
+        };
+        reader.seek(SeekFrom::Start(0))?;
+
+        if is_seven_zip {
+            Self::parse_uncompressed(&decompress(reader)?)
+        } else {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            Self::parse(&data)
+        }
+    }
+
+    /// Parse a DSF file which has been read into memory and may be
+    /// compressed with 7-Zip.
+    pub fn parse(data: &[u8]) -> Result<Self, DsfError> {
+        if data.starts_with(SEVEN_ZIP_SIGNATURE) {
+            Self::parse_uncompressed(&decompress(std::io::Cursor::new(data))?)
+        } else {
+            Self::parse_uncompressed(data)
+        }
+    }
+
+    /// Parse an uncompressed DSF file, validating its MD5 checksum.
+    fn parse_uncompressed(data: &[u8]) -> Result<Self, DsfError> {
+        let version = header(data)?;
+
+        if data.len() < HEADER_SIZE + FOOTER_SIZE {
+            return Err(DsfError::Truncated {
+                offset: HEADER_SIZE,
+                needed: FOOTER_SIZE,
+                available: data.len() - HEADER_SIZE,
+            });
+        }
+
+        let footer_offset = data.len() - FOOTER_SIZE;
+        let mut expected = [0u8; 16];
+        expected.copy_from_slice(&data[footer_offset..]);
+        let actual = md5::compute(&data[..footer_offset]).0;
+        if expected != actual {
+            return Err(DsfError::ChecksumMismatch {
+                offset: footer_offset,
+                expected,
+                actual,
+            });
+        }
+
+        let atoms = atoms(data, HEADER_SIZE, footer_offset)?;
+
+        let head =
+            find_atom(&atoms, AtomId::HEAD).ok_or(DsfError::MissingAtom { id: AtomId::HEAD })?;
+        let properties = match find_atom(&head.children, AtomId::PROP) {
+            Some(prop) => property_pairs(string_table(data, prop)?)?,
+            None => Vec::new(),
+        };
+
+        let defn = find_atom(&atoms, AtomId::DEFN);
+        let definitions = |id: AtomId| -> Result<Vec<String>, DsfError> {
+            match defn.and_then(|defn| find_atom(&defn.children, id)) {
+                Some(atom) => Ok(string_table(data, atom)?
+                    .into_iter()
+                    .map(|(_, string)| string)
+                    .collect()),
+                None => Ok(Vec::new()),
+            }
+        };
+
+        Ok(Self {
+            version,
+            properties,
+            terrain_definitions: definitions(AtomId::TERT)?,
+            object_definitions: definitions(AtomId::OBJT)?,
+            polygon_definitions: definitions(AtomId::POLY)?,
+            network_definitions: definitions(AtomId::NETW)?,
+            raster_definitions: definitions(AtomId::DEMN)?,
+            atoms,
+        })
+    }
+
+    /// The value of the first property with the specified name.
+    pub fn property<'a>(&'a self, name: &'a str) -> Option<&'a str> {
+        self.properties_named(name).next()
+    }
+
+    /// The values of all the properties with the specified name.
+    pub fn properties_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.properties
+            .iter()
+            .filter(move |(property_name, _)| property_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All the definitions (virtual or relative paths to terrain,
+    /// objects, polygons and networks) referenced by this tile.
+    pub fn definitions(&self) -> impl Iterator<Item = &str> {
+        self.terrain_definitions
+            .iter()
+            .chain(&self.object_definitions)
+            .chain(&self.polygon_definitions)
+            .chain(&self.network_definitions)
+            .map(String::as_str)
+    }
+}
+
+/// Read and parse the DSF file at the specified path, which may be
+/// compressed with 7-Zip.
+pub fn read_dsf(path: impl AsRef<Path>) -> Result<DsfFile, DsfError> {
+    let file = std::fs::File::open(path)?;
+    DsfFile::read(std::io::BufReader::new(file))
+}
+
+/// Decompress the first file in a 7-Zip archive into memory.
+fn decompress<R: Read + Seek>(mut reader: R) -> Result<Vec<u8>, DsfError> {
+    let length = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut archive = SevenZReader::new(reader, length, Password::empty())?;
+
+    let mut data: Option<Vec<u8>> = None;
+    archive.for_each_entries(|entry, entry_reader| {
+        if entry.is_directory() || data.is_some() {
+            return Ok(true);
+        }
+        // The size in the archive's header is not trusted for
+        // preallocation, as a corrupt archive could claim any size.
+        let mut buffer = Vec::new();
+        entry_reader.read_to_end(&mut buffer)?;
+        data = Some(buffer);
+        Ok(false)
+    })?;
+
+    data.ok_or(DsfError::EmptyArchive)
+}
+
+fn truncated(offset: usize, needed: usize, data: &[u8]) -> DsfError {
+    DsfError::Truncated {
+        offset,
+        needed,
+        available: data.len().saturating_sub(offset),
+    }
+}
+
+/// Parse the master header, returning the version.
+fn header(data: &[u8]) -> Result<i32, DsfError> {
+    fn cookie_and_version(input: &[u8]) -> IResult<&[u8], (&[u8], i32)> {
+        let (input, cookie) = take(COOKIE.len())(input)?;
+        let (input, version) = le_i32(input)?;
+        Ok((input, (cookie, version)))
+    }
+
+    let (_, (cookie, version)) =
+        cookie_and_version(data).map_err(|_| truncated(0, HEADER_SIZE, data))?;
+
+    if cookie != COOKIE {
+        return Err(DsfError::InvalidCookie {
+            found: cookie.to_vec(),
+        });
+    }
+
+    if version != SUPPORTED_VERSION {
+        return Err(DsfError::UnsupportedVersion {
+            offset: COOKIE.len(),
+            version,
+        });
+    }
+
+    Ok(version)
+}
+
+fn atom_header(input: &[u8]) -> IResult<&[u8], (AtomId, u32)> {
+    let (input, id) = le_u32(input)?;
+    let (input, size) = le_u32(input)?;
+    Ok((input, (AtomId::from_file_value(id), size)))
+}
+
+/// Parse the atoms between the `start` and `end` offsets of `data`,
+/// recursing into container atoms.
+fn atoms(data: &[u8], start: usize, end: usize) -> Result<Vec<Atom>, DsfError> {
+    let mut parsed = Vec::new();
+    let mut offset = start;
+
+    while offset < end {
+        let (_, (id, size)) = atom_header(&data[offset..end])
+            .map_err(|_| truncated(offset, ATOM_HEADER_SIZE, &data[..end]))?;
+        let size = size as usize;
+
+        if size < ATOM_HEADER_SIZE {
+            return Err(DsfError::InvalidAtomSize { offset, id, size });
+        }
+
+        let atom_end = offset + size;
+        if atom_end > end {
+            return Err(truncated(offset, size, &data[..end]));
+        }
+
+        let children = if id.is_container() {
+            atoms(data, offset + ATOM_HEADER_SIZE, atom_end)?
+        } else {
+            Vec::new()
+        };
+
+        parsed.push(Atom {
+            id,
+            offset,
+            size,
+            children,
+        });
+        offset = atom_end;
+    }
+
+    Ok(parsed)
+}
+
+fn find_atom(atoms: &[Atom], id: AtomId) -> Option<&Atom> {
+    atoms.iter().find(|atom| atom.id == id)
+}
+
+/// Read the null terminated strings in a string table atom, along with
+/// the offset of each string.
+fn string_table(data: &[u8], atom: &Atom) -> Result<Vec<(usize, String)>, DsfError> {
+    let start = atom.offset + ATOM_HEADER_SIZE;
+    let payload = &data[start..(atom.offset + atom.size)];
+
+    let mut strings = Vec::new();
+    let mut string_start = 0;
+    while string_start < payload.len() {
+        let length = payload[string_start..]
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(DsfError::UnterminatedString {
+                offset: start + string_start,
+            })?;
+        let string = &payload[string_start..(string_start + length)];
+        strings.push((
+            start + string_start,
+            String::from_utf8_lossy(string).into_owned(),
+        ));
+        string_start += length + 1;
+    }
+
+    Ok(strings)
+}
+
+/// Pair up the strings in a `PROP` atom into names and values.
+fn property_pairs(strings: Vec<(usize, String)>) -> Result<Vec<(String, String)>, DsfError> {
+    let mut properties = Vec::with_capacity(strings.len() / 2);
+    let mut strings = strings.into_iter();
+    while let Some((name_offset, name)) = strings.next() {
+        match strings.next() {
+            Some((_, value)) => properties.push((name, value)),
+            None => {
+                return Err(DsfError::UnpairedProperty {
+                    offset: name_offset,
+                    name,
+                })
+            }
+        }
+    }
+    Ok(properties)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+
+    use super::{read_dsf, DsfError, DsfFile, SEVEN_ZIP_SIGNATURE};
+
+    fn atom(id: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut id_value = *id;
+        id_value.reverse();
+        let mut atom = id_value.to_vec();
+        atom.extend_from_slice(&((payload.len() + 8) as u32).to_le_bytes());
+        atom.extend_from_slice(payload);
+        atom
+    }
+
+    fn string_table(id: &[u8; 4], strings: &[&str]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for string in strings {
+            payload.extend_from_slice(string.as_bytes());
+            payload.push(0);
+        }
+        atom(id, &payload)
+    }
+
+    /// Build a small but complete uncompressed DSF file.
+    fn example_dsf(properties: &[&str]) -> Vec<u8> {
+        let head = atom(b"HEAD", &string_table(b"PROP", properties));
+        let defn = atom(
+            b"DEFN",
+            &[
+                string_table(b"TERT", &["lib/g10/terrain10/fst_tmp_wet_hill.ter"]),
+                string_table(
+                    b"OBJT",
+                    &["lib/airport/vehicles/tug.obj", "objects/hangar.obj"],
+                ),
+                string_table(b"POLY", &["lib/g10/forests/conifer.for"]),
+                string_table(b"NETW", &["lib/g10/roads.net"]),
+                string_table(b"DEMN", &[]),
+            ]
+            .concat(),
+        );
+        let geod = atom(b"GEOD", &atom(b"POOL", &[0; 6]));
+        let dems = atom(b"DEMS", &[]);
+        let cmds = atom(b"CMDS", &[1, 0, 0]);
+
+        let mut data = b"XPLNEDSF".to_vec();
+        data.extend_from_slice(&1i32.to_le_bytes());
+        data.extend_from_slice(&[head, defn, geod, dems, cmds].concat());
+        let digest = md5::compute(&data);
+        data.extend_from_slice(&digest.0);
+        data
+    }
+
+    fn nzqn_dsf() -> Vec<u8> {
+        example_dsf(&[
+            "sim/west",
+            "168",
+            "sim/south",
+            "-46",
+            "sim/east",
+            "169",
+            "sim/north",
+            "-45",
+            "sim/require_object",
+            "1/0",
+            "sim/require_object",
+            "3/0",
+        ])
+    }
+
+    #[test]
+    fn test_parse() {
+        let dsf = DsfFile::parse(&nzqn_dsf()).unwrap();
+        insta::assert_json_snapshot!(dsf);
+        assert_eq!(Some("168"), dsf.property("sim/west"));
+        assert_eq!(
+            vec!["1/0", "3/0"],
+            dsf.properties_named("sim/require_object")
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(5, dsf.definitions().count());
+    }
+
+    fn compress(data: &[u8], name: &str) -> Vec<u8> {
+        let mut writer = SevenZWriter::new(Cursor::new(Vec::new())).unwrap();
+        let mut entry = SevenZArchiveEntry::new();
+        entry.name = name.to_owned();
+        entry.has_stream = true;
+        writer.push_archive_entry(entry, Some(data)).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_compressed() {
+        let data = nzqn_dsf();
+        let compressed = compress(&data, "-46+168.dsf");
+        assert!(compressed.starts_with(SEVEN_ZIP_SIGNATURE));
+
+        let expected = DsfFile::parse(&data).unwrap();
+        assert_eq!(expected, DsfFile::parse(&compressed).unwrap());
+        assert_eq!(
+            expected,
+            DsfFile::read(Cursor::new(compressed.as_slice())).unwrap()
+        );
+        assert_eq!(
+            expected,
+            DsfFile::read(Cursor::new(data.as_slice())).unwrap()
+        );
+    }
+
+    /// A reader which returns at most two bytes from each read.
+    struct ShortReads<R>(R);
+
+    impl<R: std::io::Read> std::io::Read for ShortReads<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let length = buf.len().min(2);
+            self.0.read(&mut buf[..length])
+        }
+    }
+
+    impl<R: std::io::Seek> std::io::Seek for ShortReads<R> {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_compressed_short_reads() {
+        let data = nzqn_dsf();
+        let compressed = compress(&data, "-46+168.dsf");
+        assert_eq!(
+            DsfFile::parse(&data).unwrap(),
+            DsfFile::read(ShortReads(Cursor::new(compressed.as_slice()))).unwrap()
+        );
+        assert!(matches!(
+            DsfFile::read(Cursor::new(&b"7z"[..])),
+            Err(DsfError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compressed_corrupt() {
+        let mut compressed = compress(&nzqn_dsf(), "-46+168.dsf");
+        compressed.truncate(compressed.len() / 2);
+        assert!(matches!(
+            DsfFile::parse(&compressed),
+            Err(DsfError::Decompression(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_dsf() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("-46+168.dsf");
+        std::fs::write(&path, nzqn_dsf()).unwrap();
+        assert_eq!(
+            DsfFile::parse(&nzqn_dsf()).unwrap(),
+            read_dsf(&path).unwrap()
+        );
+        assert!(matches!(
+            read_dsf(dir.path().join("missing.dsf")),
+            Err(DsfError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_cookie() {
+        let mut data = nzqn_dsf();
+        data[0] = b'Y';
+        assert!(matches!(
+            DsfFile::parse(&data),
+            Err(DsfError::InvalidCookie { .. })
+        ));
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut data = nzqn_dsf();
+        data[20] ^= 0xff;
+        let footer_offset = data.len() - 16;
+        match DsfFile::parse(&data) {
+            Err(DsfError::ChecksumMismatch { offset, .. }) => assert_eq!(footer_offset, offset),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = nzqn_dsf();
+        match DsfFile::parse(&data[..10]) {
+            Err(DsfError::Truncated {
+                offset, available, ..
+            }) => {
+                assert_eq!(0, offset);
+                assert_eq!(10, available);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        // Cut the file off part way through the `CMDS` atom, and
+        // recalculate the checksum so that the atoms are checked.
+        let mut data = data[..(data.len() - 18)].to_vec();
+        let digest = md5::compute(&data);
+        data.extend_from_slice(&digest.0);
+        let cmds_offset = data.len() - 16 - 9;
+        match DsfFile::parse(&data) {
+            Err(DsfError::Truncated {
+                offset,
+                needed,
+                available,
+            }) => {
+                assert_eq!(cmds_offset, offset);
+                assert_eq!(11, needed);
+                assert_eq!(9, available);
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unpaired_property() {
+        let data = example_dsf(&["sim/west", "168", "sim/south"]);
+        assert!(matches!(
+            DsfFile::parse(&data),
+            Err(DsfError::UnpairedProperty { .. })
+        ));
+    }
+}
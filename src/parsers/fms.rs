@@ -0,0 +1,145 @@
+//! Parser for X-Plane `.fms` flight plan files, supporting both the
+//! `3 version` format used by X-Plane 10, and the `1100 Version`
+//! format used by X-Plane 11 and later.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::{digit1, line_ending, not_line_ending, space1},
+    combinator::{eof, map_res, opt},
+    multi::many_till,
+    IResult,
+};
+use serde::Serialize;
+
+use crate::geo::LatLon;
+
+/// A waypoint in the route of a flight plan.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FmsWaypoint {
+    /// The type of waypoint, e.g. `1` for an airport, `11` for a fix,
+    /// or `28` for a latitude/longitude.
+    pub kind: u32,
+    pub ident: String,
+    pub location: LatLon,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FmsFile {
+    pub version: u64,
+    pub waypoints: Vec<FmsWaypoint>,
+}
+
+/// Parses the version line, e.g. `1100 Version` or `3 version`.
+fn version(input: &str) -> IResult<&str, u64> {
+    let (input, version) = map_res(digit1, str::parse::<u64>)(input)?;
+    let (input, _) = space1(input)?;
+    let (input, _) = alt((tag("Version"), tag("version")))(input)?;
+    Ok((input, version))
+}
+
+/// Parses a waypoint line. The latitude and longitude are always the
+/// last two fields, which are preceded by the altitude, and in newer
+/// versions the airway or procedure used to reach the waypoint.
+///
+/// e.g. `11 TEKAP DRCT 0.000000 -44.047500 170.475278` or
+/// `1 NZQN 0.000000 -45.021111 168.739167`.
+fn waypoint(input: &str) -> IResult<&str, FmsWaypoint> {
+    let (input, kind) = map_res(digit1, str::parse::<u32>)(input)?;
+    let (input, _) = space1(input)?;
+    let (input, ident) = is_not(" \t\r\n")(input)?;
+    let (input, _) = space1(input)?;
+    let (input, location) = map_res(not_line_ending, |fields: &str| {
+        let mut fields = fields.split_whitespace().rev();
+        let lon = fields.next().ok_or(())?.parse::<f64>().map_err(|_| ())?;
+        let lat = fields.next().ok_or(())?.parse::<f64>().map_err(|_| ())?;
+        Result::<LatLon, ()>::Ok(LatLon::new(lat, lon))
+    })(input)?;
+
+    let output = FmsWaypoint {
+        kind,
+        ident: ident.to_string(),
+        location,
+    };
+    Ok((input, output))
+}
+
+fn waypoint_or_other_line(input: &str) -> IResult<&str, Option<FmsWaypoint>> {
+    let (input, waypoint) = alt((
+        map_res(waypoint, |waypoint| {
+            Result::<Option<FmsWaypoint>, ()>::Ok(Some(waypoint))
+        }),
+        map_res(not_line_ending, |_| {
+            Result::<Option<FmsWaypoint>, ()>::Ok(None)
+        }),
+    ))(input)?;
+    let (input, _) = alt((line_ending, eof))(input)?;
+    Ok((input, waypoint))
+}
+
+/// Parses an `.fms` flight plan, ignoring all lines other than the
+/// waypoints of the route.
+pub fn fms_file(input: &str) -> IResult<&str, FmsFile> {
+    let (input, _) = alt((tag("I"), tag("A")))(input)?;
+    let (input, _) = line_ending(input)?;
+    let (input, version) = version(input)?;
+    let (input, _) = opt(not_line_ending)(input)?;
+    let (input, _) = line_ending(input)?;
+
+    let (input, (lines, _)) = many_till(waypoint_or_other_line, eof)(input)?;
+    let waypoints = lines.into_iter().flatten().collect();
+
+    Ok((input, FmsFile { version, waypoints }))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{fms_file, version, waypoint, FmsWaypoint};
+    use crate::geo::LatLon;
+
+    #[test]
+    fn test_version() {
+        assert_eq!(1100, version("1100 Version").unwrap().1);
+        assert_eq!(3, version("3 version").unwrap().1);
+        assert!(version("CYCLE 1710").is_err());
+    }
+
+    #[test]
+    fn test_waypoint() {
+        assert_eq!(
+            FmsWaypoint {
+                kind: 11,
+                ident: "TEKAP".to_string(),
+                location: LatLon::new(-44.0475, 170.475278),
+            },
+            waypoint("11 TEKAP DRCT 0.000000 -44.047500 170.475278")
+                .unwrap()
+                .1
+        );
+        assert!(waypoint("1 NZQN").is_err());
+    }
+
+    #[test]
+    fn test_fms_file() {
+        let fms = "I\r\n\
+            1100 Version\r\n\
+            CYCLE 1710\r\n\
+            ADEP NZQN\r\n\
+            ADES NZCH\r\n\
+            NUMENR 3\r\n\
+            1 NZQN ADEP 1171.000000 -45.021111 168.739167\r\n\
+            11 TEKAP DRCT 0.000000 -44.047500 170.475278\r\n\
+            1 NZCH ADES 123.000000 -43.489444 172.532222\r\n";
+        insta::assert_json_snapshot!(fms_file(fms).unwrap().1);
+
+        let fms = "I\n\
+            3 version\n\
+            1\n\
+            1\n\
+            1 NZQN 0.000000 -45.021111 168.739167\n\
+            1 NZCH 0.000000 -43.489444 172.532222";
+        assert_eq!(2, fms_file(fms).unwrap().1.waypoints.len());
+    }
+}
@@ -0,0 +1,562 @@
+use std::{
+    os::unix::prelude::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use eyre::Context;
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while, take_while1},
+    character::complete::{line_ending, one_of, space0},
+    combinator::{eof, map, map_res, opt, rest, value},
+    multi::many_till,
+    number::complete::double,
+    sequence::{preceded, terminated, tuple},
+    IResult,
+};
+use serde::Serialize;
+
+use super::inifile::ToIniFile;
+
+/// The way in which an export contributes its real path to a virtual
+/// path.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum ExportKind {
+    /// `EXPORT`: replaces the exports for the virtual path from
+    /// libraries lower down in the load order.
+    Export,
+    /// `EXPORT_EXTEND`: adds to the exports for the virtual path from
+    /// other libraries.
+    Extend,
+    /// `EXPORT_EXCLUDE`: replaces the exports for the virtual path,
+    /// and prevents any other library from extending it.
+    Exclude,
+    /// `EXPORT_BACKUP`: only used if no other library exports the
+    /// virtual path.
+    Backup,
+    /// `EXPORT_RATIO`: adds to the exports for the virtual path, with
+    /// a weighting relative to the other exports.
+    Ratio(f64),
+}
+
+impl ExportKind {
+    fn keyword(&self) -> &'static str {
+        match self {
+            ExportKind::Export => "EXPORT",
+            ExportKind::Extend => "EXPORT_EXTEND",
+            ExportKind::Exclude => "EXPORT_EXCLUDE",
+            ExportKind::Backup => "EXPORT_BACKUP",
+            ExportKind::Ratio(_) => "EXPORT_RATIO",
+        }
+    }
+}
+
+/// Who is able to make use of an export.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum Visibility {
+    /// `PUBLIC`: usable by any scenery pack.
+    #[default]
+    Public,
+    /// `PRIVATE`: only intended for use by the library itself.
+    Private,
+    /// `DEPRECATED`: still usable, but hidden from editing tools.
+    Deprecated,
+}
+
+impl Visibility {
+    fn keyword(&self) -> &'static str {
+        match self {
+            Visibility::Public => "PUBLIC",
+            Visibility::Private => "PRIVATE",
+            Visibility::Deprecated => "DEPRECATED",
+        }
+    }
+}
+
+/// A mapping from a virtual path to a file within the library.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LibraryExport {
+    pub kind: ExportKind,
+    /// The path that other scenery packs use to reference this
+    /// export. e.g. `lib/airport/vehicles/tug.obj`.
+    pub virtual_path: String,
+    /// Path to the exported file, relative to the library's scenery
+    /// pack directory.
+    pub real_path: PathBuf,
+    /// Name of the region (see [RegionDefinition]) that this export is
+    /// restricted to, if any.
+    pub region: Option<String>,
+    pub visibility: Visibility,
+}
+
+/// A condition which must be met for a region to apply.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub enum RegionCondition {
+    /// `REGION_ALL`: applies everywhere.
+    All,
+    /// `REGION_RECT`: applies within the specified bounds (in
+    /// degrees).
+    Rect {
+        west: f64,
+        south: f64,
+        east: f64,
+        north: f64,
+    },
+    /// `REGION_BITMAP`: applies where the specified image (relative
+    /// to the library's directory) is not black.
+    Bitmap(PathBuf),
+    /// `REGION_DREF`: applies when the dataref comparison is true.
+    /// e.g. `REGION_DREF sim/weather/temperature_sealevel_c < 0`.
+    Dref {
+        dataref: String,
+        operator: String,
+        value: f64,
+    },
+}
+
+/// A region defined with `REGION_DEFINE`, which can be used to
+/// restrict exports to a geographical area or simulator state.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RegionDefinition {
+    pub name: String,
+    pub conditions: Vec<RegionCondition>,
+}
+
+/// The contents of a scenery library's `library.txt` file.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LibraryTxt {
+    pub version: u64,
+    pub regions: Vec<RegionDefinition>,
+    pub exports: Vec<LibraryExport>,
+}
+
+impl ToIniFile for LibraryTxt {
+    type Error = eyre::Error;
+
+    /// Exports are written in order, as X-Plane chooses between exports
+    /// of the same virtual path by their order. Once a `REGION` has
+    /// been specified in the file it applies to all the exports which
+    /// follow, so an export which is not restricted to a region cannot
+    /// follow one which is, and this returns an error.
+    fn write_ini(&self, out: &mut impl std::io::Write) -> Result<(), Self::Error> {
+        out.write_all("A\n".as_bytes())?;
+        out.write_all(self.version.to_string().as_bytes())?;
+        out.write_all("\nLIBRARY\n".as_bytes())?;
+
+        for region in &self.regions {
+            out.write_all("\n".as_bytes())?;
+            region.write_ini(out)?;
+        }
+
+        let mut current_region: Option<&str> = None;
+        let mut current_visibility = Visibility::default();
+
+        out.write_all("\n".as_bytes())?;
+        for export in &self.exports {
+            if export.region.as_deref() != current_region {
+                if export.region.is_none() {
+                    return Err(eyre::eyre!(
+                        "Export of {:?} is not restricted to a region, but follows REGION {:?}",
+                        export.virtual_path,
+                        current_region.unwrap_or_default()
+                    ));
+                }
+                current_region = export.region.as_deref();
+                if let Some(region) = current_region {
+                    out.write_all("\nREGION ".as_bytes())?;
+                    out.write_all(region.as_bytes())?;
+                    out.write_all("\n".as_bytes())?;
+                }
+            }
+
+            if export.visibility != current_visibility {
+                current_visibility = export.visibility;
+                out.write_all(current_visibility.keyword().as_bytes())?;
+                out.write_all("\n".as_bytes())?;
+            }
+
+            export.write_ini(out)?;
+            out.write_all("\n".as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ToIniFile for RegionDefinition {
+    type Error = eyre::Error;
+
+    fn write_ini(&self, out: &mut impl std::io::Write) -> Result<(), Self::Error> {
+        out.write_all("REGION_DEFINE ".as_bytes())?;
+        out.write_all(self.name.as_bytes())?;
+        out.write_all("\n".as_bytes())?;
+
+        for condition in &self.conditions {
+            match condition {
+                RegionCondition::All => out.write_all("REGION_ALL".as_bytes())?,
+                RegionCondition::Rect {
+                    west,
+                    south,
+                    east,
+                    north,
+                } => write!(out, "REGION_RECT {} {} {} {}", west, south, east, north)?,
+                RegionCondition::Bitmap(path) => {
+                    out.write_all("REGION_BITMAP ".as_bytes())?;
+                    write_path(out, path)?;
+                }
+                RegionCondition::Dref {
+                    dataref,
+                    operator,
+                    value,
+                } => write!(out, "REGION_DREF {} {} {}", dataref, operator, value)?,
+            }
+            out.write_all("\n".as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ToIniFile for LibraryExport {
+    type Error = eyre::Error;
+
+    fn write_ini(&self, out: &mut impl std::io::Write) -> Result<(), Self::Error> {
+        out.write_all(self.kind.keyword().as_bytes())?;
+        out.write_all(" ".as_bytes())?;
+
+        if let ExportKind::Ratio(ratio) = self.kind {
+            write!(out, "{} ", ratio)?;
+        }
+
+        out.write_all(self.virtual_path.as_bytes())?;
+        out.write_all(" ".as_bytes())?;
+        write_path(out, &self.real_path)?;
+
+        Ok(())
+    }
+}
+
+fn write_path(out: &mut impl std::io::Write, path: &Path) -> eyre::Result<()> {
+    out.write_all(path.as_os_str().as_bytes())
+        .wrap_err_with(|| eyre::eyre!("Unable to write library path: {:?}", path))
+}
+
+/// A single line in a `library.txt` file.
+#[derive(Clone, Debug, PartialEq)]
+enum Statement {
+    Export {
+        kind: ExportKind,
+        virtual_path: String,
+        real_path: PathBuf,
+    },
+    RegionDefine(String),
+    RegionCondition(RegionCondition),
+    Region(String),
+    Visibility(Visibility),
+    /// A blank line, a comment, or a directive which is not used by
+    /// Scenable.
+    Ignored,
+}
+
+fn whitespace(input: &str) -> IResult<&str, &str> {
+    take_while1(|c| c == ' ' || c == '\t')(input)
+}
+
+fn token(input: &str) -> IResult<&str, &str> {
+    is_not(" \t\r\n")(input)
+}
+
+/// The remainder of the line, with trailing whitespace removed. Used
+/// for real paths which may contain spaces.
+fn path_until_newline(input: &str) -> IResult<&str, &Path> {
+    map(rest, |path: &str| Path::new(path.trim_end()))(input)
+}
+
+fn export_kind(input: &str) -> IResult<&str, ExportKind> {
+    alt((
+        value(ExportKind::Extend, tag("EXPORT_EXTEND")),
+        value(ExportKind::Exclude, tag("EXPORT_EXCLUDE")),
+        value(ExportKind::Backup, tag("EXPORT_BACKUP")),
+        map(
+            preceded(tuple((tag("EXPORT_RATIO"), whitespace)), double),
+            ExportKind::Ratio,
+        ),
+        value(ExportKind::Export, tag("EXPORT")),
+    ))(input)
+}
+
+/// Parse an export line.
+///
+/// e.g. `EXPORT lib/g10/terrain/grass.ter terrain/grass.ter` or
+/// `EXPORT_RATIO 0.25 lib/people/walker.obj objects/walker.obj`.
+fn export(input: &str) -> IResult<&str, Statement> {
+    let (input, kind) = export_kind(input)?;
+    let (input, _) = whitespace(input)?;
+    let (input, virtual_path) = token(input)?;
+    let (input, _) = whitespace(input)?;
+    let (input, real_path) = path_until_newline(input)?;
+
+    let output = Statement::Export {
+        kind,
+        virtual_path: virtual_path.to_owned(),
+        real_path: real_path.to_owned(),
+    };
+
+    Ok((input, output))
+}
+
+fn region_condition(input: &str) -> IResult<&str, RegionCondition> {
+    let rect = map(
+        tuple((
+            tag("REGION_RECT"),
+            preceded(whitespace, double),
+            preceded(whitespace, double),
+            preceded(whitespace, double),
+            preceded(whitespace, double),
+        )),
+        |(_, west, south, east, north)| RegionCondition::Rect {
+            west,
+            south,
+            east,
+            north,
+        },
+    );
+    let bitmap = map(
+        preceded(
+            tuple((tag("REGION_BITMAP"), whitespace)),
+            path_until_newline,
+        ),
+        |path| RegionCondition::Bitmap(path.to_owned()),
+    );
+    let dref = map(
+        tuple((
+            tag("REGION_DREF"),
+            preceded(whitespace, token),
+            preceded(whitespace, token),
+            preceded(whitespace, double),
+        )),
+        |(_, dataref, operator, value): (_, &str, &str, f64)| RegionCondition::Dref {
+            dataref: dataref.to_owned(),
+            operator: operator.to_owned(),
+            value,
+        },
+    );
+
+    alt((
+        value(RegionCondition::All, tag("REGION_ALL")),
+        rect,
+        bitmap,
+        dref,
+    ))(input)
+}
+
+fn visibility(input: &str) -> IResult<&str, Visibility> {
+    let (input, visibility) = alt((
+        value(Visibility::Public, tag("PUBLIC")),
+        value(Visibility::Private, tag("PRIVATE")),
+        value(Visibility::Deprecated, tag("DEPRECATED")),
+    ))(input)?;
+    // Newer versions of X-Plane allow a version number to follow the
+    // visibility, which is not required here.
+    let (input, _) = rest(input)?;
+    Ok((input, visibility))
+}
+
+/// Parse the contents of a single line (excluding the line ending).
+fn statement(input: &str) -> IResult<&str, Statement> {
+    let region_define = map(
+        preceded(tuple((tag("REGION_DEFINE"), whitespace)), token),
+        |name: &str| Statement::RegionDefine(name.to_owned()),
+    );
+    let region = map(
+        preceded(tuple((tag("REGION"), whitespace)), token),
+        |name: &str| Statement::Region(name.to_owned()),
+    );
+    let ignored = value(Statement::Ignored, rest);
+
+    alt((
+        export,
+        region_define,
+        map(region_condition, Statement::RegionCondition),
+        region,
+        map(visibility, Statement::Visibility),
+        ignored,
+    ))(input)
+}
+
+fn line(input: &str) -> IResult<&str, Statement> {
+    let (input, content) =
+        terminated(take_while(|c| c != '\r' && c != '\n'), opt(line_ending))(input)?;
+    let (_, statement) = statement(content.trim_start())?;
+    Ok((input, statement))
+}
+
+fn version_number(input: &str) -> IResult<&str, u64> {
+    map_res(take_while1(|c: char| c.is_ascii_digit()), |s: &str| {
+        s.parse::<u64>()
+    })(input)
+}
+
+/// Parse a `library.txt` file.
+///
+/// Comments, blank lines, and directives which are not relevant to
+/// Scenable are skipped.
+pub fn library_txt(input: &str) -> IResult<&str, LibraryTxt> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let (input, _) = one_of("AI")(input)?;
+    let (input, _) = preceded(space0, line_ending)(input)?;
+    let (input, version) = version_number(input)?;
+    let (input, _) = preceded(space0, line_ending)(input)?;
+    let (input, _) = tag("LIBRARY")(input)?;
+    let (input, _) = preceded(space0, alt((line_ending, eof)))(input)?;
+
+    let (input, (statements, _)) = many_till(line, eof)(input)?;
+
+    let mut library = LibraryTxt {
+        version,
+        regions: Vec::new(),
+        exports: Vec::new(),
+    };
+    let mut region: Option<String> = None;
+    let mut visibility = Visibility::default();
+
+    for statement in statements {
+        match statement {
+            Statement::Export {
+                kind,
+                virtual_path,
+                real_path,
+            } => library.exports.push(LibraryExport {
+                kind,
+                virtual_path,
+                real_path,
+                region: region.clone(),
+                visibility,
+            }),
+            Statement::RegionDefine(name) => library.regions.push(RegionDefinition {
+                name,
+                conditions: Vec::new(),
+            }),
+            Statement::RegionCondition(condition) => {
+                // Conditions without a preceding `REGION_DEFINE` are
+                // meaningless and are dropped.
+                if let Some(definition) = library.regions.last_mut() {
+                    definition.conditions.push(condition);
+                }
+            }
+            Statement::Region(name) => region = Some(name),
+            Statement::Visibility(new_visibility) => visibility = new_visibility,
+            Statement::Ignored => {}
+        }
+    }
+
+    Ok((input, library))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        export, library_txt, region_condition, statement, ExportKind, LibraryExport,
+        RegionCondition, Statement, Visibility,
+    };
+    use crate::parsers::inifile::ToIniFile;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_library_txt() {
+        let library_file = std::fs::read_to_string("src/parsers/library.txt").unwrap();
+        insta::assert_json_snapshot!(library_txt(&library_file).unwrap().1);
+    }
+
+    #[test]
+    fn test_export() {
+        insta::assert_debug_snapshot!(
+            export("EXPORT_BACKUP lib/airport/vehicles/tug.obj objects/vehicles/tug 2.obj  ")
+                .unwrap()
+                .1
+        );
+        insta::assert_debug_snapshot!(
+            export("EXPORT_RATIO 0.5 lib/airport/people/walker.obj objects/walker.obj")
+                .unwrap()
+                .1
+        );
+        assert!(export("EXPORT lib/airport/vehicles/tug.obj").is_err());
+    }
+
+    #[test]
+    fn test_region_condition() {
+        assert_eq!(
+            RegionCondition::Rect {
+                west: -10.5,
+                south: 35.0,
+                east: 30.0,
+                north: 70.0
+            },
+            region_condition("REGION_RECT -10.5 35 30 70").unwrap().1
+        );
+        assert_eq!(
+            RegionCondition::All,
+            region_condition("REGION_ALL").unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_statement_ignored() {
+        assert_eq!(Statement::Ignored, statement("# A comment").unwrap().1);
+        assert_eq!(Statement::Ignored, statement("").unwrap().1);
+        assert_eq!(
+            Statement::Ignored,
+            statement("EXPORT_SEASON summer lib/a.obj b.obj").unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_to_ini_round_trip() {
+        let library_file = std::fs::read_to_string("src/parsers/library.txt").unwrap();
+        let library = library_txt(&library_file).unwrap().1;
+
+        let mut buffer: Vec<u8> = Vec::new();
+        library.write_ini(&mut buffer).unwrap();
+        let written = std::str::from_utf8(&buffer).unwrap();
+        insta::assert_snapshot!(written);
+
+        assert_eq!(library, library_txt(written).unwrap().1);
+    }
+
+    #[test]
+    fn test_to_ini_unrestricted_after_region() {
+        let mut library = library_txt("A\n800\nLIBRARY\n").unwrap().1;
+        library.exports = vec![
+            LibraryExport {
+                kind: ExportKind::Export,
+                virtual_path: "lib/a.obj".to_string(),
+                real_path: "a_nz.obj".into(),
+                region: Some("new_zealand".to_string()),
+                visibility: Visibility::Public,
+            },
+            LibraryExport {
+                kind: ExportKind::Export,
+                virtual_path: "lib/a.obj".to_string(),
+                real_path: "a.obj".into(),
+                region: None,
+                visibility: Visibility::Public,
+            },
+        ];
+        assert!(library.write_ini(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_library_txt_header() {
+        for header in &[
+            "\u{feff}A\n800\nLIBRARY\n",
+            "A  \r\n800 \r\nLIBRARY\r\n",
+            "I\t\n800\nLIBRARY  \n",
+            "A\n800\nLIBRARY \t",
+        ] {
+            let library = library_txt(header).unwrap().1;
+            assert_eq!(800, library.version, "{:?}", header);
+            assert!(library.exports.is_empty());
+        }
+        assert!(library_txt("A\n800\nLIBRARY_X\n").is_err());
+    }
+}
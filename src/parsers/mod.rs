@@ -1,2 +1,7 @@
+pub mod apt_dat;
+pub mod art_asset;
+pub mod dsf;
+pub mod fms;
 pub mod inifile;
+pub mod library;
 pub mod scenery_packs;
@@ -1,4 +1,5 @@
 use std::{
+    ffi::OsStr,
     os::unix::prelude::OsStrExt,
     path::{Path, PathBuf},
 };
@@ -7,60 +8,131 @@ use eyre::Context;
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag, take_while},
-    character::complete::newline,
     combinator::{eof, map_res},
-    multi::many_till,
+    error::ErrorKind,
+    sequence::tuple,
     IResult,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::inifile::ToIniFile;
-
-fn version_number(input: &str) -> IResult<&str, u64> {
-    map_res(take_while(char::is_numeric), |s: &str| s.parse::<u64>())(input)
+use crate::xplane::XPlaneVersion;
+
+fn version_number(input: &[u8]) -> IResult<&[u8], u64> {
+    map_res(take_while(|c: u8| c.is_ascii_digit()), |s: &[u8]| {
+        std::str::from_utf8(s)
+            .map_err(|_| ())?
+            .parse::<u64>()
+            .map_err(|_| ())
+    })(input)
 }
 
 /// Parses a version number specification.
 ///
 /// e.g. `1000 Version`
-fn version(input: &str) -> IResult<&str, u64> {
+fn version(input: &[u8]) -> IResult<&[u8], u64> {
     let (input, version_number) = version_number(input)?;
     let (input, _) = tag(" Version")(input)?;
     Ok((input, version_number))
 }
 
+/// The version number written in the header of a new
+/// `scenery_packs.ini`.
+pub const DEFAULT_VERSION: u64 = 1000;
+
+/// The special scenery pack entry which loads X-Plane's global
+/// airports.
+pub const GLOBAL_AIRPORTS_PATH: &str = "*GLOBAL_AIRPORTS*";
+
 /// A scenery pack in the x-plane.
-#[derive(Serialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct SceneryPack {
     /// Whether or not this scenery pack is enabled.
     pub enabled: bool,
-    /// Path to this scenery pack relative to the x-plane root
-    /// directory.
+    /// Path to this scenery pack exactly as it appears in
+    /// `scenery_packs.ini`. See [SceneryPack::location()] for what it
+    /// refers to.
     pub path: PathBuf,
 }
 
+impl SceneryPack {
+    /// A scenery pack for use in tests.
+    #[cfg(test)]
+    pub fn new(path: &str, enabled: bool) -> Self {
+        Self {
+            enabled,
+            path: path.into(),
+        }
+    }
+
+    pub fn location(&self) -> SceneryPackPath<'_> {
+        SceneryPackPath::new(&self.path)
+    }
+}
+
+/// What the path of a [SceneryPack] refers to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneryPackPath<'a> {
+    /// A path relative to the X-Plane directory, e.g. `Custom
+    /// Scenery/Some Pack/`.
+    Relative(&'a Path),
+    /// An absolute path to a scenery pack outside of the X-Plane
+    /// directory, e.g. on another drive.
+    Absolute(&'a Path),
+    /// The `*GLOBAL_AIRPORTS*` entry.
+    GlobalAirports,
+    /// Any other special entry of the form `*NAME*`, which does not
+    /// refer to a directory.
+    Special(&'a str),
+}
+
+impl<'a> SceneryPackPath<'a> {
+    pub fn new(path: &'a Path) -> Self {
+        match path.to_str() {
+            Some(GLOBAL_AIRPORTS_PATH) => Self::GlobalAirports,
+            Some(name) if name.len() > 1 && name.starts_with('*') && name.ends_with('*') => {
+                Self::Special(name)
+            }
+            _ if path.is_absolute() => Self::Absolute(path),
+            _ => Self::Relative(path),
+        }
+    }
+
+    /// The directory of the scenery pack in the X-Plane installation
+    /// at `xplane_dir`, whose version is `xplane_version`, or `None`
+    /// for special entries which are not directories.
+    pub fn resolve(&self, xplane_dir: &Path, xplane_version: XPlaneVersion) -> Option<PathBuf> {
+        match self {
+            Self::Relative(path) => Some(xplane_dir.join(path)),
+            Self::Absolute(path) => Some(path.to_path_buf()),
+            Self::GlobalAirports => Some(xplane_version.global_airports_dir(xplane_dir)),
+            Self::Special(_) => None,
+        }
+    }
+}
+
 impl ToIniFile for SceneryPack {
     type Error = eyre::Error;
 
     fn write_ini(&self, out: &mut impl std::io::Write) -> Result<(), Self::Error> {
         match self.enabled {
             true => {
-                out.write("SCENERY_PACK".as_bytes())?;
+                out.write_all("SCENERY_PACK".as_bytes())?;
             }
             false => {
-                out.write("SCENERY_PACK_DISABLED".as_bytes())?;
+                out.write_all("SCENERY_PACK_DISABLED".as_bytes())?;
             }
         }
 
-        out.write(" ".as_bytes())?;
-        out.write(self.path.as_os_str().as_bytes())
+        out.write_all(" ".as_bytes())?;
+        out.write_all(self.path.as_os_str().as_bytes())
             .wrap_err_with(|| eyre::eyre!("Unable to write scenery pack path: {:?}", self.path))?;
 
         Ok(())
     }
 }
 
-fn scenery_pack_enabled(input: &str) -> IResult<&str, bool> {
+fn scenery_pack_enabled(input: &[u8]) -> IResult<&[u8], bool> {
     let enabled_tag = map_res(tag("SCENERY_PACK"), |_| Result::<bool, ()>::Ok(true));
     let disabled_tag = map_res(tag("SCENERY_PACK_DISABLED"), |_| {
         Result::<bool, ()>::Ok(false)
@@ -69,9 +141,9 @@ fn scenery_pack_enabled(input: &str) -> IResult<&str, bool> {
     alt((disabled_tag, enabled_tag))(input)
 }
 
-fn until_newline(input: &str) -> IResult<&str, &Path> {
-    let (input, path_str) = is_not("\r\n")(input)?;
-    let path = Path::new(path_str);
+fn until_newline(input: &[u8]) -> IResult<&[u8], &Path> {
+    let (input, path_bytes) = is_not("\r\n")(input)?;
+    let path = Path::new(OsStr::from_bytes(path_bytes));
 
     Ok((input, path))
 }
@@ -80,7 +152,7 @@ fn until_newline(input: &str) -> IResult<&str, &Path> {
 ///
 /// e.g. `SCENERY_PACK Custom Scenery/Some Pack` or
 /// `SCENERY_PACK_DISABLED Custom Scenery/Some Pack`.
-fn scenery_pack(input: &str) -> IResult<&str, SceneryPack> {
+fn scenery_pack(input: &[u8]) -> IResult<&[u8], SceneryPack> {
     let (input, enabled) = scenery_pack_enabled(input)?;
     let (input, _) = tag(" ")(input)?;
     let (input, path) = until_newline(input)?;
@@ -93,57 +165,355 @@ fn scenery_pack(input: &str) -> IResult<&str, SceneryPack> {
     Ok((input, output))
 }
 
-fn scenery_pack_or_newline(input: &str) -> IResult<&str, Option<SceneryPack>> {
-    alt((
-        map_res(newline, |_| Result::<Option<SceneryPack>, ()>::Ok(None)),
-        map_res(scenery_pack, |pack| {
-            Result::<Option<SceneryPack>, ()>::Ok(Some(pack))
-        }),
-    ))(input)
+/// The characters which end a line in the ini file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// What a line in the ini file contains.
+#[derive(Clone, Debug, PartialEq)]
+enum IniLineKind {
+    /// The version line, as it was read. It is written as read unless
+    /// the version has changed.
+    Version(Vec<u8>),
+    /// A scenery pack line. When the file is written, each of these is
+    /// replaced by the next of [SceneryPacksIni::scenery_packs].
+    SceneryPack,
+    /// Any other line, such as the header, a blank line, a comment or
+    /// a directive which is not understood. These are written exactly
+    /// as read.
+    Other(Vec<u8>),
+    /// A scenery pack line which could not be parsed. See
+    /// [SceneryPacksIni::invalid_lines].
+    Invalid(Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct IniLine {
+    kind: IniLineKind,
+    /// `None` for a last line without a line ending.
+    ending: Option<LineEnding>,
+}
+
+impl IniLine {
+    fn other(text: &[u8]) -> Self {
+        Self {
+            kind: IniLineKind::Other(text.to_owned()),
+            ending: Some(LineEnding::Lf),
+        }
+    }
+}
+
+/// Split `input` into lines, without their line endings.
+fn lines(input: &[u8]) -> impl Iterator<Item = (&[u8], Option<LineEnding>)> {
+    input
+        .split_inclusive(|c| *c == b'\n')
+        .map(|line| match line.strip_suffix(b"\n") {
+            None => (line, None),
+            Some(line) => match line.strip_suffix(b"\r") {
+                Some(line) => (line, Some(LineEnding::CrLf)),
+                None => (line, Some(LineEnding::Lf)),
+            },
+        })
+}
+
+/// Parse `line_contents` completely with `parser`.
+fn whole_line<'a, O>(
+    parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+    line_contents: &'a [u8],
+) -> IResult<&'a [u8], O> {
+    let (input, (output, _)) = tuple((parser, eof))(line_contents)?;
+    Ok((input, output))
+}
+
+/// An error in a line of `scenery_packs.ini`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IniParseError {
+    /// The line number, starting from 1.
+    pub line: usize,
+    /// The column where the error was found, in characters starting
+    /// from 1.
+    pub column: usize,
+    /// The contents of the line, which may not be valid UTF-8.
+    pub text: Vec<u8>,
+    /// What was expected at `column`.
+    pub expected: &'static str,
+}
+
+impl IniParseError {
+    /// Create an error for the line `text`, from the `error` returned
+    /// by a parser given that line. `expected` describes what the
+    /// parser expected, given the kind of error.
+    fn new(
+        line: usize,
+        text: &[u8],
+        error: nom::Err<nom::error::Error<&[u8]>>,
+        expected: impl FnOnce(ErrorKind) -> &'static str,
+    ) -> Self {
+        let (remaining, kind) = match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => (error.input, error.code),
+            nom::Err::Incomplete(_) => (&[][..], ErrorKind::Eof),
+        };
+        let offset = text.len().saturating_sub(remaining.len());
+        Self {
+            line,
+            column: String::from_utf8_lossy(&text[..offset]).chars().count() + 1,
+            text: text.to_owned(),
+            expected: expected(kind),
+        }
+    }
+}
+
+impl std::fmt::Display for IniParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: expected {} in {:?}",
+            self.line,
+            self.column,
+            self.expected,
+            String::from_utf8_lossy(&self.text)
+        )
+    }
 }
 
+impl std::error::Error for IniParseError {}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct SceneryPacksIni {
     pub version: u64,
     pub scenery_packs: Vec<SceneryPack>,
+    /// The layout of the file that this was read from, so that it can
+    /// be written back without losing anything that was not parsed.
+    #[serde(skip)]
+    lines: Vec<IniLine>,
+    /// Lines which look like scenery packs, but could not be parsed.
+    #[serde(skip)]
+    pub invalid_lines: Vec<IniParseError>,
+    /// Whether to write the [invalid_lines](Self::invalid_lines)
+    /// exactly as they were read, rather than leaving them out.
+    #[serde(skip)]
+    pub keep_invalid_lines: bool,
+}
+
+impl SceneryPacksIni {
+    /// A new ini file with the standard layout.
+    pub fn new(version: u64, scenery_packs: Vec<SceneryPack>) -> Self {
+        Self {
+            version,
+            scenery_packs,
+            lines: vec![
+                IniLine::other(b"I"),
+                IniLine {
+                    kind: IniLineKind::Version(Vec::new()),
+                    ending: Some(LineEnding::Lf),
+                },
+                IniLine::other(b"SCENERY"),
+                IniLine::other(b""),
+            ],
+            invalid_lines: Vec::new(),
+            keep_invalid_lines: true,
+        }
+    }
+
+    /// A copy of this ini file with different scenery packs, keeping
+    /// the layout of the file it was read from.
+    pub fn with_scenery_packs(&self, scenery_packs: Vec<SceneryPack>) -> Self {
+        Self {
+            scenery_packs,
+            ..self.clone()
+        }
+    }
+
+    /// The line ending used for lines which were not in the file that
+    /// this was read from.
+    fn line_ending(&self) -> LineEnding {
+        self.lines
+            .iter()
+            .find_map(|line| line.ending)
+            .unwrap_or(LineEnding::Lf)
+    }
 }
 
 impl ToIniFile for SceneryPacksIni {
     type Error = eyre::Error;
 
+    /// Writes the file in the layout that it was read with. Scenery
+    /// packs take the places of the scenery pack lines in order, any
+    /// extra scenery packs are written after the last of those lines,
+    /// and any unused scenery pack lines are left out. Invalid lines
+    /// are left out unless [SceneryPacksIni::keep_invalid_lines].
     fn write_ini(&self, out: &mut impl std::io::Write) -> Result<(), Self::Error> {
-        out.write("I\n".as_bytes())?;
-
-        out.write(self.version.to_string().as_bytes())?;
-        out.write(" Version\n".as_bytes())?;
+        let line_ending = self.line_ending();
+        let last_scenery_pack_line = self
+            .lines
+            .iter()
+            .rposition(|line| line.kind == IniLineKind::SceneryPack)
+            .unwrap_or_else(|| self.lines.len().saturating_sub(1));
+        let mut scenery_packs = self.scenery_packs.iter();
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let written = match &line.kind {
+                IniLineKind::Version(text) => {
+                    if whole_line(version, text).ok().map(|(_, version)| version)
+                        == Some(self.version)
+                    {
+                        out.write_all(text)?;
+                    } else {
+                        write!(out, "{} Version", self.version)?;
+                    }
+                    true
+                }
+                IniLineKind::SceneryPack => match scenery_packs.next() {
+                    Some(pack) => {
+                        pack.write_ini(out)?;
+                        true
+                    }
+                    None => false,
+                },
+                IniLineKind::Other(text) => {
+                    out.write_all(text)?;
+                    true
+                }
+                IniLineKind::Invalid(text) => {
+                    if self.keep_invalid_lines {
+                        out.write_all(text)?;
+                    }
+                    self.keep_invalid_lines
+                }
+            };
+            if written {
+                if let Some(ending) = line.ending {
+                    out.write_all(ending.as_str().as_bytes())?;
+                }
+            }
 
-        out.write("SCENERY\n\n".as_bytes())?;
+            if index == last_scenery_pack_line {
+                // Whether the previous line was ended, so that another
+                // can follow it. Only the last line has no ending.
+                let mut ended = !written || line.ending.is_some();
+                for pack in &mut scenery_packs {
+                    if !ended {
+                        out.write_all(line_ending.as_str().as_bytes())?;
+                    }
+                    pack.write_ini(out)?;
+                    out.write_all(line_ending.as_str().as_bytes())?;
+                    ended = true;
+                }
+            }
+        }
 
-        for pack in &self.scenery_packs {
+        // Only reached when there are no lines at all.
+        for pack in scenery_packs {
             pack.write_ini(out)?;
-            out.write("\n".as_bytes())?;
+            out.write_all(line_ending.as_str().as_bytes())?;
         }
 
         Ok(())
     }
 }
 
-pub fn scenery_packs_ini(input: &str) -> IResult<&str, SceneryPacksIni> {
-    let (input, _) = tag("I")(input)?;
-    let (input, _) = newline(input)?;
-    let (input, version_number) = version(input)?;
-    let (input, _) = newline(input)?;
-    let (input, _) = tag("SCENERY")(input)?;
-    let (input, _) = newline(input)?;
+/// Parse a header line of the ini file, `line` being `None` if the
+/// file has ended.
+fn header_line<'a, O>(
+    number: usize,
+    line: Option<(&'a [u8], Option<LineEnding>)>,
+    parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+    expected: &'static str,
+) -> Result<(&'a [u8], Option<LineEnding>, O), IniParseError> {
+    let (text, ending) = line.unwrap_or((&[], None));
+    let (_, output) = whole_line(parser, text)
+        .map_err(|error| IniParseError::new(number, text, error, |_| expected))?;
+    Ok((text, ending, output))
+}
 
-    let (input, (lines, _)) = many_till(scenery_pack_or_newline, eof)(input)?;
-    let scenery_packs: Vec<SceneryPack> = lines.into_iter().filter_map(|line| line).collect();
+/// Parse a `scenery_packs.ini` file. An invalid header is an error,
+/// but invalid scenery pack lines are collected in
+/// [SceneryPacksIni::invalid_lines] so that the rest of the file can
+/// still be used.
+pub fn scenery_packs_ini(input: &[u8]) -> Result<SceneryPacksIni, IniParseError> {
+    let mut input_lines = lines(input);
+    let (header, header_ending, _) = header_line(1, input_lines.next(), tag("I"), "`I`")?;
+    let (version_line, version_ending, version_number) =
+        header_line(2, input_lines.next(), version, "`<number> Version`")?;
+    let (scenery, scenery_ending, _) =
+        header_line(3, input_lines.next(), tag("SCENERY"), "`SCENERY`")?;
+
+    let mut lines = vec![
+        IniLine {
+            kind: IniLineKind::Other(header.to_owned()),
+            ending: header_ending,
+        },
+        IniLine {
+            kind: IniLineKind::Version(version_line.to_owned()),
+            ending: version_ending,
+        },
+        IniLine {
+            kind: IniLineKind::Other(scenery.to_owned()),
+            ending: scenery_ending,
+        },
+    ];
+    let mut scenery_packs = Vec::new();
+    let mut invalid_lines = Vec::new();
+
+    for (number, (text, ending)) in (4..).zip(input_lines) {
+        let kind = match whole_line(scenery_pack, text) {
+            Ok((_, pack)) => {
+                scenery_packs.push(pack);
+                IniLineKind::SceneryPack
+            }
+            Err(error) if text.starts_with(b"SCENERY_PACK") => {
+                invalid_lines.push(IniParseError::new(number, text, error, |kind| match kind {
+                    ErrorKind::Tag => "` ` after the directive",
+                    ErrorKind::IsNot => "the scenery pack's path",
+                    _ => "the end of the line",
+                }));
+                IniLineKind::Invalid(text.to_owned())
+            }
+            Err(_) => IniLineKind::Other(text.to_owned()),
+        };
+        lines.push(IniLine { kind, ending });
+    }
 
-    let output = SceneryPacksIni {
+    Ok(SceneryPacksIni {
         version: version_number,
         scenery_packs,
-    };
-    Ok((input, output))
+        lines,
+        invalid_lines,
+        keep_invalid_lines: true,
+    })
+}
+
+/// The path of `scenery_packs.ini` in the X-Plane installation at
+/// `xplane_dir`.
+pub fn scenery_packs_ini_path(xplane_dir: &Path) -> PathBuf {
+    xplane_dir.join("Custom Scenery").join("scenery_packs.ini")
+}
+
+/// Read and parse the `scenery_packs.ini` file at `ini_path`. Invalid
+/// scenery pack lines are logged, and returned in
+/// [SceneryPacksIni::invalid_lines].
+pub fn read_scenery_packs_ini(ini_path: impl AsRef<Path>) -> eyre::Result<SceneryPacksIni> {
+    let ini_path = ini_path.as_ref();
+    tracing::info!("Reading scenery packs from {:?}", ini_path);
+    let ini_file_bytes = std::fs::read(ini_path)?;
+    let ini = scenery_packs_ini(&ini_file_bytes)
+        .wrap_err_with(|| eyre::eyre!("Error parsing {:?}", ini_path))?;
+    for error in &ini.invalid_lines {
+        tracing::warn!("Invalid line in {:?}: {}", ini_path, error);
+    }
+    Ok(ini)
 }
 
 #[cfg(test)]
@@ -151,34 +521,74 @@ mod test {
     use std::path::Path;
 
     use super::{
-        scenery_pack, scenery_pack_enabled, scenery_packs_ini, until_newline, version, SceneryPack,
-        SceneryPacksIni, ToIniFile,
+        scenery_pack, scenery_pack_enabled, scenery_packs_ini, until_newline, version,
+        IniParseError, SceneryPack, SceneryPackPath, SceneryPacksIni, ToIniFile,
     };
+    use crate::xplane::XPlaneVersion;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_scenery_pack_path() {
+        let xplane_dir = Path::new("/X-Plane 12");
+        let relative = SceneryPackPath::new(Path::new("Custom Scenery/NZQN/"));
+        assert_eq!(
+            SceneryPackPath::Relative(Path::new("Custom Scenery/NZQN/")),
+            relative
+        );
+        assert_eq!(
+            Some(Path::new("/X-Plane 12/Custom Scenery/NZQN/").to_path_buf()),
+            relative.resolve(xplane_dir, XPlaneVersion::X12)
+        );
+
+        let absolute = SceneryPackPath::new(Path::new("/mnt/scenery/Ortho4XP/"));
+        assert_eq!(
+            SceneryPackPath::Absolute(Path::new("/mnt/scenery/Ortho4XP/")),
+            absolute
+        );
+        assert_eq!(
+            Some(Path::new("/mnt/scenery/Ortho4XP/").to_path_buf()),
+            absolute.resolve(xplane_dir, XPlaneVersion::X12)
+        );
+
+        let global_airports = SceneryPackPath::new(Path::new("*GLOBAL_AIRPORTS*"));
+        assert_eq!(SceneryPackPath::GlobalAirports, global_airports);
+        assert_eq!(
+            Some(Path::new("/X-Plane 12/Global Scenery/Global Airports").to_path_buf()),
+            global_airports.resolve(xplane_dir, XPlaneVersion::X12)
+        );
+        assert_eq!(
+            Some(Path::new("/X-Plane 12/Custom Scenery/Global Airports").to_path_buf()),
+            global_airports.resolve(xplane_dir, XPlaneVersion::X11)
+        );
+
+        let special = SceneryPackPath::new(Path::new("*SOMETHING_ELSE*"));
+        assert_eq!(SceneryPackPath::Special("*SOMETHING_ELSE*"), special);
+        assert_eq!(None, special.resolve(xplane_dir, XPlaneVersion::X12));
+    }
+
     #[test]
     fn test_version() {
-        assert_eq!(1000, version("1000 Version").unwrap().1);
-        assert!(version("1000").is_err());
+        assert_eq!(1000, version(b"1000 Version").unwrap().1);
+        assert!(version(b"1000").is_err());
     }
 
     #[test]
     fn test_scenery_packs_ini() {
         let ini_file = std::fs::read_to_string("src/parsers/scenery_packs.ini").unwrap();
-        insta::assert_json_snapshot!(scenery_packs_ini(&ini_file).unwrap().1);
+        insta::assert_json_snapshot!(scenery_packs_ini(ini_file.as_bytes()).unwrap());
     }
 
     #[test]
     fn test_scenery_pack_enabled() {
-        assert!(scenery_pack_enabled("SCENERY_PACK").unwrap().1);
-        assert!(!scenery_pack_enabled("SCENERY_PACK_DISABLED").unwrap().1);
+        assert!(scenery_pack_enabled(b"SCENERY_PACK").unwrap().1);
+        assert!(!scenery_pack_enabled(b"SCENERY_PACK_DISABLED").unwrap().1);
     }
 
     #[test]
     fn test_path_until_newline() {
         assert_eq!(
             Path::new("Custom Scenery/-Canberra_Beacons/"),
-            until_newline("Custom Scenery/-Canberra_Beacons/\n")
+            until_newline(b"Custom Scenery/-Canberra_Beacons/\n")
                 .unwrap()
                 .1
         );
@@ -186,12 +596,12 @@ mod test {
 
     #[test]
     fn test_scenery_pack() {
-        let enabled_pack = scenery_pack("SCENERY_PACK Custom Scenery/-Canberra_Beacons/\n")
+        let enabled_pack = scenery_pack(b"SCENERY_PACK Custom Scenery/-Canberra_Beacons/\n")
             .unwrap()
             .1;
         insta::assert_json_snapshot!(enabled_pack);
         let disabled_pack =
-            scenery_pack("SCENERY_PACK_DISABLED Custom Scenery/A New Zealand NZMC Mount Cook/\n")
+            scenery_pack(b"SCENERY_PACK_DISABLED Custom Scenery/A New Zealand NZMC Mount Cook/\n")
                 .unwrap()
                 .1;
         insta::assert_json_snapshot!(disabled_pack);
@@ -209,10 +619,7 @@ mod test {
             path: "Example 2".into(),
         };
 
-        let scenery_packs_ini = SceneryPacksIni {
-            version: 1000,
-            scenery_packs: vec![pack1, pack2],
-        };
+        let scenery_packs_ini = SceneryPacksIni::new(1000, vec![pack1, pack2]);
 
         let mut buffer: Vec<u8> = Vec::new();
 
@@ -229,4 +636,124 @@ SCENERY_PACK_DISABLED Example 2
 "#;
         assert_eq!(expected, ini_file)
     }
+
+    fn to_ini_string(ini: &SceneryPacksIni) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        ini.write_ini(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ini_file = std::fs::read_to_string("src/parsers/scenery_packs.ini").unwrap();
+        let ini = scenery_packs_ini(ini_file.as_bytes()).unwrap();
+        assert_eq!(ini_file, to_ini_string(&ini));
+
+        let ini_file = "I\r\n1100 Version\r\nSCENERY\r\n\r\n\
+            # A comment\r\n\
+            SCENERY_PACK Custom Scenery/A/  \r\n\
+            \r\n\
+            SCENERY_PACK_DISABLED Custom Scenery/B/\r\n\
+            UNKNOWN_DIRECTIVE something\r\n\
+            SCENERY_PACK Custom Scenery/C/";
+        let ini = scenery_packs_ini(ini_file.as_bytes()).unwrap();
+        assert_eq!(3, ini.scenery_packs.len());
+        assert_eq!(Path::new("Custom Scenery/A/  "), ini.scenery_packs[0].path);
+        assert_eq!(ini_file, to_ini_string(&ini));
+    }
+
+    #[test]
+    fn test_round_trip_modified() {
+        let ini_file = "I\r\n1000 Version\r\nSCENERY\r\n\r\n\
+            SCENERY_PACK A\r\n\
+            # A comment\r\n\
+            SCENERY_PACK B\r\n\
+            SCENERY_PACK C\r\n\
+            \r\n";
+        let ini = scenery_packs_ini(ini_file.as_bytes()).unwrap();
+        let reordered = ini.with_scenery_packs(vec![
+            SceneryPack::new("C", true),
+            SceneryPack::new("A", false),
+            SceneryPack::new("B", true),
+            SceneryPack::new("D", true),
+        ]);
+        assert_eq!(
+            "I\r\n1000 Version\r\nSCENERY\r\n\r\n\
+            SCENERY_PACK C\r\n\
+            # A comment\r\n\
+            SCENERY_PACK_DISABLED A\r\n\
+            SCENERY_PACK B\r\n\
+            SCENERY_PACK D\r\n\
+            \r\n",
+            to_ini_string(&reordered)
+        );
+
+        let mut removed = ini.with_scenery_packs(vec![SceneryPack::new("B", true)]);
+        removed.version = 1100;
+        assert_eq!(
+            "I\r\n1100 Version\r\nSCENERY\r\n\r\n\
+            SCENERY_PACK B\r\n\
+            # A comment\r\n\
+            \r\n",
+            to_ini_string(&removed)
+        );
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let error = scenery_packs_ini(b"I\n1000 Versoin\nSCENERY\n").unwrap_err();
+        assert_eq!(
+            IniParseError {
+                line: 2,
+                column: 5,
+                text: b"1000 Versoin".to_vec(),
+                expected: "`<number> Version`",
+            },
+            error
+        );
+        assert_eq!(3, scenery_packs_ini(b"I\n1000 Version\n").unwrap_err().line);
+
+        let ini_file = "I\n1000 Version\nSCENERY\n\n\
+            SCENERY_PACK A\n\
+            SCENERY_PACKB\n\
+            SCENERY_PACK_DISABLED \n\
+            SCENERY_PACK C\n";
+        let mut ini = scenery_packs_ini(ini_file.as_bytes()).unwrap();
+        assert_eq!(2, ini.scenery_packs.len());
+        let invalid: Vec<(usize, usize, &str)> = ini
+            .invalid_lines
+            .iter()
+            .map(|error| (error.line, error.column, error.expected))
+            .collect();
+        assert_eq!(
+            vec![
+                (6, 13, "` ` after the directive"),
+                (7, 23, "the scenery pack's path")
+            ],
+            invalid
+        );
+
+        assert_eq!(ini_file, to_ini_string(&ini));
+        ini.keep_invalid_lines = false;
+        assert_eq!(
+            "I\n1000 Version\nSCENERY\n\nSCENERY_PACK A\nSCENERY_PACK C\n",
+            to_ini_string(&ini)
+        );
+    }
+
+    #[test]
+    fn test_non_utf8() {
+        // `Custom Scenery/LFPO Orly Améliorée/` encoded as Latin-1.
+        let ini_file: &[u8] =
+            b"I\n1000 Version\nSCENERY\n\nSCENERY_PACK Custom Scenery/LFPO Orly Am\xe9lior\xe9e/\n";
+        let ini = scenery_packs_ini(ini_file).unwrap();
+        assert_eq!(
+            "Custom Scenery/LFPO Orly Am\u{FFFD}lior\u{FFFD}e/",
+            ini.scenery_packs[0].path.to_string_lossy()
+        );
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ini.write_ini(&mut buffer).unwrap();
+        assert_eq!(ini_file, buffer);
+    }
 }
@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use std::{collections::HashSet, path::PathBuf};
+
+use crate::{
+    parsers::scenery_packs::SceneryPack,
+    settings::{read_config_file, write_config_file},
+};
+
+/// A named snapshot of the order and enabled state of scenery packs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    /// Scenery packs in load order.
+    pub scenery_packs: Vec<SceneryPack>,
+}
+
+impl Preset {
+    /// Apply this preset to `scenery_packs`. Scenery packs in the
+    /// preset are placed first, in the order and with the enabled state
+    /// stored in the preset. Scenery packs which are not in the preset
+    /// (e.g. installed after it was created) follow in their current
+    /// order, and are left unchanged. Scenery packs in the preset which
+    /// are no longer present in `scenery_packs` are ignored.
+    pub fn apply(&self, scenery_packs: &im_rc::Vector<SceneryPack>) -> im_rc::Vector<SceneryPack> {
+        let current_paths: HashSet<&PathBuf> = scenery_packs
+            .iter()
+            .map(|scenery_pack| &scenery_pack.path)
+            .collect();
+        let preset_paths: HashSet<&PathBuf> = self
+            .scenery_packs
+            .iter()
+            .map(|scenery_pack| &scenery_pack.path)
+            .collect();
+
+        let from_preset = self
+            .scenery_packs
+            .iter()
+            .filter(|scenery_pack| current_paths.contains(&scenery_pack.path));
+        let not_in_preset = scenery_packs
+            .iter()
+            .filter(|scenery_pack| !preset_paths.contains(&scenery_pack.path));
+
+        from_preset.chain(not_in_preset).cloned().collect()
+    }
+}
+
+/// The user's presets, stored in `presets.ron` alongside the
+/// [Settings](crate::settings::Settings) file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Presets {
+    pub presets: Vec<Preset>,
+}
+
+impl Presets {
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Preset> {
+        self.presets.iter_mut().find(|preset| preset.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Create a new preset, or return an error if a preset with the
+    /// same name already exists.
+    pub fn create(&mut self, preset: Preset) -> eyre::Result<()> {
+        if self.contains(&preset.name) {
+            return Err(eyre::eyre!(
+                "A preset named {:?} already exists",
+                preset.name
+            ));
+        }
+        self.presets.push(preset);
+        Ok(())
+    }
+
+    /// Replace the scenery packs stored in an existing preset.
+    pub fn update(&mut self, name: &str, scenery_packs: Vec<SceneryPack>) -> eyre::Result<()> {
+        let preset = self
+            .get_mut(name)
+            .ok_or_else(|| eyre::eyre!("No preset named {:?} exists", name))?;
+        preset.scenery_packs = scenery_packs;
+        Ok(())
+    }
+
+    /// Add scenery packs to an existing preset. Scenery packs which are
+    /// already in the preset have their enabled state replaced, and the
+    /// others are appended in the order given.
+    pub fn add_scenery_packs(
+        &mut self,
+        name: &str,
+        scenery_packs: impl IntoIterator<Item = SceneryPack>,
+    ) -> eyre::Result<()> {
+        let preset = self
+            .get_mut(name)
+            .ok_or_else(|| eyre::eyre!("No preset named {:?} exists", name))?;
+        for scenery_pack in scenery_packs {
+            match preset
+                .scenery_packs
+                .iter_mut()
+                .find(|preset_pack| preset_pack.path == scenery_pack.path)
+            {
+                Some(preset_pack) => *preset_pack = scenery_pack,
+                None => preset.scenery_packs.push(scenery_pack),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: String) -> eyre::Result<()> {
+        if self.contains(&new_name) {
+            return Err(eyre::eyre!("A preset named {:?} already exists", new_name));
+        }
+        let preset = self
+            .get_mut(name)
+            .ok_or_else(|| eyre::eyre!("No preset named {:?} exists", name))?;
+        preset.name = new_name;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> eyre::Result<()> {
+        let index = self
+            .presets
+            .iter()
+            .position(|preset| preset.name == name)
+            .ok_or_else(|| eyre::eyre!("No preset named {:?} exists", name))?;
+        self.presets.remove(index);
+        Ok(())
+    }
+
+    /// Create a copy of an existing preset with a new name, placed
+    /// directly after the original.
+    pub fn duplicate(&mut self, name: &str, new_name: String) -> eyre::Result<()> {
+        if self.contains(&new_name) {
+            return Err(eyre::eyre!("A preset named {:?} already exists", new_name));
+        }
+        let index = self
+            .presets
+            .iter()
+            .position(|preset| preset.name == name)
+            .ok_or_else(|| eyre::eyre!("No preset named {:?} exists", name))?;
+        let mut preset = self.presets[index].clone();
+        preset.name = new_name;
+        self.presets.insert(index + 1, preset);
+        Ok(())
+    }
+
+    /// Generate a name based on `name` which is not used by any
+    /// existing preset.
+    pub fn unique_name(&self, name: &str) -> String {
+        if !self.contains(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|number| format!("{} ({})", name, number))
+            .find(|candidate| !self.contains(candidate))
+            .expect("Unable to generate a unique preset name")
+    }
+
+    pub fn from_presets_file() -> eyre::Result<Self> {
+        Ok(read_config_file("presets.ron")?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        write_config_file("presets.ron", self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{Preset, Presets};
+    use crate::parsers::scenery_packs::SceneryPack;
+
+    #[test]
+    fn test_apply() {
+        let preset = Preset {
+            name: "New Zealand".to_string(),
+            scenery_packs: vec![
+                SceneryPack::new("Custom Scenery/NZQN/", true),
+                SceneryPack::new("Custom Scenery/Removed/", true),
+                SceneryPack::new("Custom Scenery/EDDF/", false),
+            ],
+        };
+
+        let current: im_rc::Vector<SceneryPack> = vec![
+            SceneryPack::new("Custom Scenery/EDDF/", true),
+            SceneryPack::new("Custom Scenery/New/", true),
+            SceneryPack::new("Custom Scenery/NZQN/", false),
+        ]
+        .into();
+
+        let expected: im_rc::Vector<SceneryPack> = vec![
+            SceneryPack::new("Custom Scenery/NZQN/", true),
+            SceneryPack::new("Custom Scenery/EDDF/", false),
+            SceneryPack::new("Custom Scenery/New/", true),
+        ]
+        .into();
+
+        assert_eq!(expected, preset.apply(&current));
+    }
+
+    #[test]
+    fn test_presets() {
+        let mut presets = Presets::default();
+        let preset = Preset {
+            name: "Europe".to_string(),
+            scenery_packs: vec![SceneryPack::new("Custom Scenery/EDDF/", true)],
+        };
+        presets.create(preset.clone()).unwrap();
+        assert!(presets.create(preset).is_err());
+
+        presets.duplicate("Europe", "Europe 2".to_string()).unwrap();
+        assert_eq!("Europe (2)", presets.unique_name("Europe"));
+        presets.rename("Europe 2", "Germany".to_string()).unwrap();
+        assert!(presets.rename("Germany", "Europe".to_string()).is_err());
+        presets
+            .update(
+                "Germany",
+                vec![SceneryPack::new("Custom Scenery/EDDF/", false)],
+            )
+            .unwrap();
+        presets
+            .add_scenery_packs(
+                "Germany",
+                vec![
+                    SceneryPack::new("Custom Scenery/EDDM/", true),
+                    SceneryPack::new("Custom Scenery/EDDF/", true),
+                ],
+            )
+            .unwrap();
+        presets.delete("Europe").unwrap();
+
+        assert_eq!(1, presets.presets.len());
+        assert_eq!("Germany", presets.presets[0].name);
+        assert_eq!(
+            vec![
+                SceneryPack::new("Custom Scenery/EDDF/", true),
+                SceneryPack::new("Custom Scenery/EDDM/", true),
+            ],
+            presets.presets[0].scenery_packs
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Resolving a route, entered as text or read from an `.fms` flight
+//! plan, into the locations of its waypoints.
+
+use std::{
+    collections::HashMap,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use eyre::Context;
+
+use crate::{geo::LatLon, parsers::fms::fms_file, scan::SceneryScan};
+
+/// Named fixes from X-Plane's navigation data. Fix names are not
+/// unique, so each name may have several locations.
+#[derive(Clone, Debug, Default)]
+pub struct Fixes {
+    fixes: HashMap<String, Vec<LatLon>>,
+}
+
+impl Fixes {
+    /// Read the fixes from `earth_fix.dat`, preferring the updated
+    /// navigation data in `Custom Data` over the data which ships with
+    /// X-Plane in `Resources/default data`. Returns no fixes if
+    /// neither file exists.
+    pub fn read(xplane_dir: impl AsRef<Path>) -> eyre::Result<Self> {
+        let xplane_dir = xplane_dir.as_ref();
+        let paths: [PathBuf; 2] = [
+            xplane_dir.join("Custom Data").join("earth_fix.dat"),
+            xplane_dir
+                .join("Resources")
+                .join("default data")
+                .join("earth_fix.dat"),
+        ];
+
+        match paths.iter().find(|path| path.is_file()) {
+            Some(path) => Self::read_earth_fix_dat(path),
+            None => {
+                tracing::warn!("No earth_fix.dat found in {:?}", xplane_dir);
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// Read an `earth_fix.dat` file, in which each fix is a line
+    /// starting with its latitude, longitude and name.
+    fn read_earth_fix_dat(path: &Path) -> eyre::Result<Self> {
+        tracing::info!("Reading fixes from {:?}", path);
+        let file =
+            std::fs::File::open(path).wrap_err_with(|| eyre::eyre!("Unable to open {:?}", path))?;
+        let mut reader = std::io::BufReader::new(file);
+
+        let mut fixes = Self::default();
+        let mut line_bytes = Vec::new();
+        loop {
+            line_bytes.clear();
+            if reader.read_until(b'\n', &mut line_bytes)? == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&line_bytes);
+            if let Some((name, location)) = fix_line(&line) {
+                fixes.fixes.entry(name).or_default().push(location);
+            }
+        }
+
+        Ok(fixes)
+    }
+
+    /// The location of the fix with the specified name which is
+    /// closest to `near`, or the first one found if `near` is `None`.
+    pub fn find(&self, name: &str, near: Option<&LatLon>) -> Option<LatLon> {
+        let locations = self.fixes.get(name)?;
+        match near {
+            Some(near) => locations.iter().copied().min_by(|a, b| {
+                a.distance_nm(near)
+                    .partial_cmp(&b.distance_nm(near))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+            None => locations.first().copied(),
+        }
+    }
+}
+
+/// Parse a line of `earth_fix.dat` into the name and location of the
+/// fix, or `None` if the line is not a fix (e.g. the header).
+fn fix_line(line: &str) -> Option<(String, LatLon)> {
+    let mut fields = line.split_whitespace();
+    let lat = fields.next()?.parse().ok()?;
+    let lon = fields.next()?.parse().ok()?;
+    let name = fields.next()?;
+    Some((name.to_string(), LatLon::new(lat, lon)))
+}
+
+/// Parse a latitude/longitude waypoint, e.g. `-45.02/168.74`.
+fn lat_lon(waypoint: &str) -> Option<LatLon> {
+    let (lat, lon) = waypoint.split_once('/')?;
+    Some(LatLon::new(lat.parse().ok()?, lon.parse().ok()?))
+}
+
+/// Resolve a route entered as text: a list of waypoints separated by
+/// whitespace. Each waypoint is either a latitude/longitude (e.g.
+/// `-45.02/168.74`), the identifier of an airport in one of the
+/// scanned scenery packs, or the name of a fix. Where a fix name is
+/// ambiguous, the fix closest to the previous waypoint is used.
+pub fn parse_route(route: &str, scan: &SceneryScan, fixes: &Fixes) -> eyre::Result<Vec<LatLon>> {
+    let mut locations: Vec<LatLon> = Vec::new();
+    let mut unknown: Vec<&str> = Vec::new();
+
+    for waypoint in route.split_whitespace() {
+        let location = lat_lon(waypoint)
+            .or_else(|| scan.airport_location(waypoint))
+            .or_else(|| fixes.find(waypoint, locations.last()));
+        match location {
+            Some(location) => locations.push(location),
+            None => unknown.push(waypoint),
+        }
+    }
+
+    if !unknown.is_empty() {
+        return Err(eyre::eyre!("Unknown waypoints: {}", unknown.join(", ")));
+    }
+
+    if locations.is_empty() {
+        return Err(eyre::eyre!("The route does not contain any waypoints"));
+    }
+
+    Ok(locations)
+}
+
+/// Read the locations of the waypoints in an `.fms` flight plan.
+pub fn read_fms(path: impl AsRef<Path>) -> eyre::Result<Vec<LatLon>> {
+    let path = path.as_ref();
+    let fms_bytes =
+        std::fs::read(path).wrap_err_with(|| eyre::eyre!("Unable to read {:?}", path))?;
+    let fms_string = String::from_utf8_lossy(&fms_bytes);
+    let (_, fms) = fms_file(&fms_string)
+        .map_err(|error| eyre::eyre!("Error parsing {:?}: {}", path, error))?;
+    Ok(fms
+        .waypoints
+        .into_iter()
+        .map(|waypoint| waypoint.location)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::{parse_route, Fixes};
+    use crate::{
+        geo::LatLon,
+        parsers::apt_dat::{Airport, AirportKind},
+        scan::{SceneryPackScan, SceneryScan},
+    };
+
+    #[test]
+    fn test_parse_route() {
+        let mut scan = SceneryScan::default();
+        scan.packs.insert(
+            "Custom Scenery/NZQN/".into(),
+            SceneryPackScan {
+                airports: vec![Airport {
+                    kind: AirportKind::Airport,
+                    ident: "NZQN".to_string(),
+                    name: "Queenstown".to_string(),
+                    elevation: 1171,
+                    runways: Vec::new(),
+                    datum: Some(LatLon::new(-45.0, 168.7)),
+                    icao_code: None,
+                    region: None,
+                }],
+                ..Default::default()
+            },
+        );
+
+        let mut fixes = Fixes::default();
+        fixes.fixes.insert(
+            "TEKAP".to_string(),
+            vec![LatLon::new(50.0, 10.0), LatLon::new(-44.0, 170.5)],
+        );
+
+        assert_eq!(
+            vec![
+                LatLon::new(-45.0, 168.7),
+                LatLon::new(-44.0, 170.5),
+                LatLon::new(-43.5, 172.5),
+            ],
+            parse_route("NZQN TEKAP\n-43.5/172.5", &scan, &fixes).unwrap()
+        );
+        assert!(parse_route("NZQN NOWHERE", &scan, &fixes).is_err());
+        assert!(parse_route(" ", &scan, &fixes).is_err());
+    }
+}
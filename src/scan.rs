@@ -0,0 +1,574 @@
+//! Scanning of scenery pack directories on disk, to find out what
+//! each scenery pack provides and what it references.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    os::unix::prelude::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use eyre::Context;
+
+use crate::{
+    classify::{classify, SceneryCategory},
+    geo::{GeoArea, LatLon, Tile},
+    parsers::{
+        apt_dat::{read_apt_dat, Airport},
+        art_asset::{read_art_asset_references, ART_ASSET_EXTENSIONS},
+        dsf::read_dsf,
+        library::{library_txt, LibraryTxt},
+        scenery_packs::SceneryPackPath,
+    },
+    xplane::XPlaneVersion,
+};
+
+/// Information gathered from a scenery pack's DSF tile.
+#[derive(Clone, Debug, Default)]
+pub struct TileScan {
+    /// Whether the tile is an overlay (`sim/overlay 1`), rather than
+    /// base mesh.
+    pub overlay: bool,
+    /// Whether the tile uses terrain (`.ter`) definitions which are
+    /// provided by the scenery pack itself, as orthophoto scenery
+    /// does.
+    pub local_terrain: bool,
+    /// The tile covered by the DSF, from its `sim/south` and
+    /// `sim/west` properties, or its file name.
+    pub tile: Option<Tile>,
+}
+
+/// Information gathered by scanning a scenery pack's directory.
+#[derive(Clone, Debug, Default)]
+pub struct SceneryPackScan {
+    /// The contents of the pack's `library.txt`, if it is a library.
+    pub library: Option<LibraryTxt>,
+    /// The pack's DSF tiles which could be read.
+    pub tiles: Vec<TileScan>,
+    /// The categories that the scenery pack belongs to. See
+    /// [classify()].
+    pub categories: BTreeSet<SceneryCategory>,
+    /// Definitions referenced by the pack's DSF tiles, and files
+    /// referenced by its art assets (objects, facades, forests and so
+    /// on), which are not files within the pack itself, and so need to
+    /// be provided by a library.
+    pub external_references: BTreeSet<String>,
+    /// Airports defined in the pack's `Earth nav data/apt.dat`.
+    pub airports: Vec<Airport>,
+}
+
+impl SceneryPackScan {
+    /// Whether any of the pack's DSF tiles or airports intersect
+    /// `area`, or `None` if the pack has no known location (e.g. a
+    /// library).
+    pub fn intersects(&self, area: &GeoArea) -> Option<bool> {
+        let mut tiles = self.tiles.iter().filter_map(|tile| tile.tile).peekable();
+        if tiles.peek().is_none() && self.airports.is_empty() {
+            return None;
+        }
+
+        let intersects = tiles.any(|tile| area.intersects_tile(&tile))
+            || self
+                .airports
+                .iter()
+                .filter_map(Airport::location)
+                .any(|location| area.contains(&location));
+        Some(intersects)
+    }
+}
+
+/// The result of scanning all the scenery packs in an X-Plane
+/// installation.
+#[derive(Clone, Debug, Default)]
+pub struct SceneryScan {
+    /// Scans of scenery packs, keyed by [SceneryPack::path](crate::parsers::scenery_packs::SceneryPack::path).
+    pub packs: HashMap<PathBuf, SceneryPackScan>,
+    /// Virtual paths exported by the libraries which ship with
+    /// X-Plane in `Resources/default scenery`, and are always
+    /// available.
+    pub default_exports: HashSet<String>,
+    /// Scenery packs which are installed: the directories in `Custom
+    /// Scenery`, and the other scenery pack paths which were scanned
+    /// and exist, each with its canonical directory. Several paths may
+    /// refer to the same directory, such as `*GLOBAL_AIRPORTS*` and
+    /// `Custom Scenery/Global Airports/`. `None` if the scan has not
+    /// yet been performed.
+    pub installed: Option<BTreeMap<PathBuf, PathBuf>>,
+}
+
+impl SceneryScan {
+    /// Find the location of the airport with the specified identifier
+    /// in any of the scanned scenery packs.
+    pub fn airport_location(&self, ident: &str) -> Option<LatLon> {
+        self.packs
+            .values()
+            .flat_map(|pack_scan| &pack_scan.airports)
+            .filter(|airport| {
+                airport.ident.eq_ignore_ascii_case(ident)
+                    || airport.icao().eq_ignore_ascii_case(ident)
+            })
+            .find_map(Airport::location)
+    }
+}
+
+/// Read a `library.txt` file.
+pub fn read_library_txt(path: impl AsRef<Path>) -> eyre::Result<LibraryTxt> {
+    let path = path.as_ref();
+    let library_bytes =
+        std::fs::read(path).wrap_err_with(|| eyre::eyre!("Unable to read {:?}", path))?;
+    let library_string = String::from_utf8_lossy(&library_bytes);
+    let (_, library) = library_txt(&library_string)
+        .map_err(|error| eyre::eyre!("Error parsing {:?}: {}", path, error))?;
+    Ok(library)
+}
+
+/// Find the DSF tiles within a scenery pack's directory, returning
+/// their paths relative to the pack's directory.
+fn find_tiles(pack_dir: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let earth_nav_data = pack_dir.join("Earth nav data");
+    if !earth_nav_data.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut tiles = Vec::new();
+    for tile_group in std::fs::read_dir(&earth_nav_data)? {
+        let tile_group = tile_group?.path();
+        if !tile_group.is_dir() {
+            continue;
+        }
+        for tile in std::fs::read_dir(&tile_group)? {
+            let tile = tile?.path();
+            let is_dsf = tile
+                .extension()
+                .map(|extension| extension.eq_ignore_ascii_case("dsf"))
+                .unwrap_or(false);
+            if is_dsf {
+                tiles.push(tile.strip_prefix(pack_dir)?.to_owned());
+            }
+        }
+    }
+    tiles.sort();
+
+    Ok(tiles)
+}
+
+/// Find the art asset files (see [ART_ASSET_EXTENSIONS]) in
+/// `pack_dir` and its subdirectories, returning their paths relative
+/// to `pack_dir`. Symbolic links to directories are not followed, so
+/// that a link to an ancestor directory cannot make this loop forever.
+fn find_art_assets(pack_dir: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let mut art_assets = Vec::new();
+    let mut dirs = vec![pack_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let is_art_asset = path
+                .extension()
+                .map(|extension| {
+                    ART_ASSET_EXTENSIONS.iter().any(|art_asset_extension| {
+                        extension.eq_ignore_ascii_case(art_asset_extension)
+                    })
+                })
+                .unwrap_or(false);
+            if is_art_asset {
+                art_assets.push(path.strip_prefix(pack_dir)?.to_owned());
+            }
+        }
+    }
+    art_assets.sort();
+
+    Ok(art_assets)
+}
+
+/// Scan the scenery pack located in `pack_dir`.
+pub fn scan_scenery_pack(pack_dir: impl AsRef<Path>) -> eyre::Result<SceneryPackScan> {
+    let pack_dir = pack_dir.as_ref();
+
+    let library_path = pack_dir.join("library.txt");
+    let library = if library_path.is_file() {
+        match read_library_txt(&library_path) {
+            Ok(library) => Some(library),
+            Err(error) => {
+                tracing::warn!("{:?}", error);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let tile_paths = find_tiles(pack_dir)
+        .wrap_err_with(|| eyre::eyre!("Unable to find DSF tiles in {:?}", pack_dir))?;
+
+    let mut tiles = Vec::with_capacity(tile_paths.len());
+    let mut external_references = BTreeSet::new();
+    for tile_path in tile_paths {
+        let dsf = match read_dsf(pack_dir.join(&tile_path)) {
+            Ok(dsf) => dsf,
+            Err(error) => {
+                tracing::warn!(
+                    "Unable to read DSF file {:?}: {}",
+                    pack_dir.join(&tile_path),
+                    error
+                );
+                continue;
+            }
+        };
+
+        for definition in dsf.definitions() {
+            if !external_references.contains(definition) && !pack_dir.join(definition).exists() {
+                external_references.insert(definition.to_owned());
+            }
+        }
+
+        let parse_property = |name| {
+            dsf.property(name)
+                .and_then(|value| value.parse::<i32>().ok())
+        };
+        let tile = match (parse_property("sim/south"), parse_property("sim/west")) {
+            (Some(south), Some(west)) => Some(Tile { south, west }),
+            _ => tile_path
+                .file_name()
+                .and_then(|file_name| Tile::from_file_name(&file_name.to_string_lossy())),
+        };
+
+        tiles.push(TileScan {
+            tile,
+            overlay: dsf.property("sim/overlay") == Some("1"),
+            local_terrain: dsf
+                .terrain_definitions
+                .iter()
+                .any(|terrain| !external_references.contains(terrain.as_str())),
+        });
+    }
+
+    let art_asset_paths = find_art_assets(pack_dir)
+        .wrap_err_with(|| eyre::eyre!("Unable to find art assets in {:?}", pack_dir))?;
+    for art_asset_path in art_asset_paths {
+        let art_asset_path = pack_dir.join(art_asset_path);
+        let references = match read_art_asset_references(&art_asset_path) {
+            Ok(references) => references,
+            Err(error) => {
+                tracing::warn!("{:?}", error);
+                continue;
+            }
+        };
+        // References are relative to the art asset file, unless they
+        // are virtual paths exported by a library.
+        let art_asset_dir = art_asset_path.parent().unwrap_or(pack_dir);
+        for reference in references {
+            if !external_references.contains(&reference)
+                && !art_asset_dir.join(&reference).exists()
+                && !pack_dir.join(&reference).exists()
+            {
+                external_references.insert(reference);
+            }
+        }
+    }
+
+    let apt_dat_path = pack_dir.join("Earth nav data").join("apt.dat");
+    let airports = if apt_dat_path.is_file() {
+        match read_apt_dat(&apt_dat_path) {
+            Ok(airports) => airports,
+            Err(error) => {
+                tracing::warn!("{:?}", error);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let mut pack_scan = SceneryPackScan {
+        library,
+        tiles,
+        categories: BTreeSet::new(),
+        external_references,
+        airports,
+    };
+    pack_scan.categories = classify(pack_dir, &pack_scan);
+
+    Ok(pack_scan)
+}
+
+/// Read the virtual paths exported by the libraries which ship with
+/// X-Plane (see [XPlaneVersion::default_library_dirs()]). If the
+/// version of X-Plane cannot be detected, only its `Resources/default
+/// scenery` directory is read.
+pub fn scan_default_libraries(xplane_dir: impl AsRef<Path>) -> eyre::Result<HashSet<String>> {
+    let xplane_dir = xplane_dir.as_ref();
+    let version = XPlaneVersion::detect(xplane_dir).unwrap_or(XPlaneVersion::X11);
+    let mut exports = HashSet::new();
+
+    for default_scenery in version.default_library_dirs(xplane_dir) {
+        if !default_scenery.is_dir() {
+            tracing::warn!(
+                "Default scenery directory {:?} does not exist",
+                default_scenery
+            );
+            continue;
+        }
+
+        for library_dir in std::fs::read_dir(&default_scenery)? {
+            let library_path = library_dir?.path().join("library.txt");
+            if !library_path.is_file() {
+                continue;
+            }
+            match read_library_txt(&library_path) {
+                Ok(library) => exports.extend(
+                    library
+                        .exports
+                        .into_iter()
+                        .map(|export| export.virtual_path),
+                ),
+                Err(error) => tracing::warn!("{}", error),
+            }
+        }
+    }
+
+    Ok(exports)
+}
+
+/// Find the scenery pack directories in X-Plane's `Custom Scenery`
+/// directory, returning their paths relative to `xplane_dir` in the
+/// same format as `scenery_packs.ini` (e.g. `Custom Scenery/NZQN/`).
+pub fn find_custom_scenery(xplane_dir: impl AsRef<Path>) -> eyre::Result<BTreeSet<PathBuf>> {
+    let custom_scenery = xplane_dir.as_ref().join("Custom Scenery");
+    let mut packs = BTreeSet::new();
+
+    for entry in std::fs::read_dir(&custom_scenery)
+        .wrap_err_with(|| eyre::eyre!("Unable to read {:?}", custom_scenery))?
+    {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name.as_bytes().starts_with(b".") || !entry.path().is_dir() {
+            continue;
+        }
+        // Joining an empty path adds the trailing separator.
+        packs.insert(PathBuf::from("Custom Scenery").join(name).join(""));
+    }
+
+    Ok(packs)
+}
+
+/// Scan the specified scenery packs (paths as in `scenery_packs.ini`,
+/// see [SceneryPackPath]),
+/// any other scenery packs installed in `Custom Scenery`, and
+/// X-Plane's default libraries. Scenery packs which cannot be scanned
+/// are logged and omitted from the result.
+pub fn scan_scenery(xplane_dir: impl AsRef<Path>, pack_paths: Vec<PathBuf>) -> SceneryScan {
+    let xplane_dir = xplane_dir.as_ref();
+    let xplane_version = XPlaneVersion::detect(xplane_dir).unwrap_or(XPlaneVersion::X11);
+    let mut scan = SceneryScan::default();
+
+    match scan_default_libraries(xplane_dir) {
+        Ok(exports) => scan.default_exports = exports,
+        Err(error) => tracing::error!("Error scanning default libraries: {}", error),
+    }
+
+    let custom_scenery = find_custom_scenery(xplane_dir).unwrap_or_else(|error| {
+        tracing::error!("Error finding installed scenery packs: {:?}", error);
+        BTreeSet::new()
+    });
+
+    // Each directory is scanned once, as the listed scenery pack which
+    // refers to it if there is one, otherwise as it was found in
+    // `Custom Scenery`.
+    let mut installed: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+    let mut scanned: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut scanned_dirs: HashSet<PathBuf> = HashSet::new();
+    for pack_path in pack_paths.into_iter().chain(custom_scenery) {
+        if installed.contains_key(&pack_path) {
+            continue;
+        }
+        let pack_dir = match SceneryPackPath::new(&pack_path)
+            .resolve(xplane_dir, xplane_version)
+            .and_then(|dir| dir.canonicalize().ok())
+        {
+            Some(pack_dir) if pack_dir.is_dir() => pack_dir,
+            _ => continue,
+        };
+        if scanned_dirs.insert(pack_dir.clone()) {
+            scanned.push((pack_path.clone(), pack_dir.clone()));
+        }
+        installed.insert(pack_path, pack_dir);
+    }
+
+    for (pack_path, pack_dir) in &scanned {
+        match scan_scenery_pack(pack_dir) {
+            Ok(pack_scan) => {
+                scan.packs.insert(pack_path.clone(), pack_scan);
+            }
+            Err(error) => {
+                tracing::error!("Error scanning scenery pack {:?}: {:?}", pack_path, error)
+            }
+        }
+    }
+    scan.installed = Some(installed);
+
+    scan
+}
+
+/// Runs [scan_scenery()] in a background thread, for use with an
+/// immediate mode gui. The result is polled using
+/// [SceneryScanner::check()].
+#[derive(Default)]
+pub struct SceneryScanner {
+    receiver: Option<crossbeam_channel::Receiver<SceneryScan>>,
+}
+
+impl SceneryScanner {
+    /// Start scanning the specified scenery packs. `callback` is
+    /// called in the scanning thread once the scan is complete. Does
+    /// nothing if a scan is already running.
+    pub fn start<C>(&mut self, xplane_dir: PathBuf, pack_paths: Vec<PathBuf>, callback: C)
+    where
+        C: FnOnce() + Send + 'static,
+    {
+        if self.receiver.is_some() {
+            tracing::warn!("A scenery scan is already running");
+            return;
+        }
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            tracing::info!("Scanning {} scenery packs", pack_paths.len());
+            let scan = scan_scenery(&xplane_dir, pack_paths);
+            tracing::info!("Finished scanning scenery packs");
+            if sender.send(scan).is_err() {
+                tracing::warn!("Scenery scan result was not received");
+            }
+            callback();
+        });
+
+        self.receiver = Some(receiver);
+    }
+
+    /// Check if the scan is complete. If it is complete it will return
+    /// `Some` with the result of the scan, otherwise will return
+    /// `None`.
+    pub fn check(&mut self) -> Option<SceneryScan> {
+        match self.receiver.take() {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(scan) => Some(scan),
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    tracing::error!("Scenery scan channel disconnected");
+                    None
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {
+                    self.receiver = Some(receiver);
+                    None
+                }
+            },
+            None => None,
+        }
+    }
+
+    /// Returns `true` if a scan is currently running. Requires a
+    /// previous call of [SceneryScanner::check()] to update the
+    /// current status.
+    pub fn is_scanning(&self) -> bool {
+        self.receiver.is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{ffi::OsStr, os::unix::prelude::OsStrExt, path::PathBuf};
+
+    use pretty_assertions::assert_eq;
+
+    use super::{find_art_assets, find_custom_scenery, scan_scenery, scan_scenery_pack};
+
+    #[test]
+    fn test_scan_scenery_aliases() {
+        let xplane_dir = tempfile::tempdir().unwrap();
+        let custom_scenery = xplane_dir.path().join("Custom Scenery");
+        std::fs::create_dir_all(custom_scenery.join("Global Airports")).unwrap();
+        std::fs::create_dir_all(custom_scenery.join("NZQN")).unwrap();
+
+        let scan = scan_scenery(
+            xplane_dir.path(),
+            vec![
+                PathBuf::from("*GLOBAL_AIRPORTS*"),
+                custom_scenery.join("NZQN"),
+            ],
+        );
+
+        // Each directory is scanned once, as the listed scenery pack.
+        let mut scanned: Vec<&PathBuf> = scan.packs.keys().collect();
+        scanned.sort();
+        assert_eq!(
+            vec![
+                &custom_scenery.join("NZQN"),
+                &PathBuf::from("*GLOBAL_AIRPORTS*")
+            ],
+            scanned
+        );
+
+        let installed = scan.installed.unwrap();
+        assert_eq!(4, installed.len());
+        assert_eq!(
+            installed[&PathBuf::from("*GLOBAL_AIRPORTS*")],
+            installed[&PathBuf::from("Custom Scenery/Global Airports/")]
+        );
+        assert_eq!(
+            installed[&custom_scenery.join("NZQN")],
+            installed[&PathBuf::from("Custom Scenery/NZQN/")]
+        );
+    }
+
+    #[test]
+    fn test_find_custom_scenery() {
+        let xplane_dir = tempfile::tempdir().unwrap();
+        let custom_scenery = xplane_dir.path().join("Custom Scenery");
+        let non_utf8 = OsStr::from_bytes(b"Caf\xe9 Scenery");
+        std::fs::create_dir_all(custom_scenery.join(non_utf8)).unwrap();
+        std::fs::create_dir_all(custom_scenery.join("NZQN")).unwrap();
+        std::fs::create_dir_all(custom_scenery.join(".hidden")).unwrap();
+        std::fs::write(custom_scenery.join("readme.txt"), "").unwrap();
+
+        let packs: Vec<PathBuf> = find_custom_scenery(xplane_dir.path())
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            vec![
+                PathBuf::from(OsStr::from_bytes(b"Custom Scenery/Caf\xe9 Scenery/")),
+                PathBuf::from("Custom Scenery/NZQN/"),
+            ],
+            packs
+        );
+        // The paths keep their trailing separator, as in
+        // `scenery_packs.ini`.
+        assert!(packs[1].as_os_str().as_bytes().ends_with(b"/"));
+    }
+
+    #[test]
+    fn test_scan_scenery_pack_invalid_library() {
+        let pack_dir = tempfile::tempdir().unwrap();
+        std::fs::write(pack_dir.path().join("library.txt"), "not a library").unwrap();
+
+        let pack_scan = scan_scenery_pack(pack_dir.path()).unwrap();
+        assert!(pack_scan.library.is_none());
+    }
+
+    #[test]
+    fn test_find_art_assets_symlink_cycle() {
+        let pack_dir = tempfile::tempdir().unwrap();
+        let objects = pack_dir.path().join("objects");
+        std::fs::create_dir_all(&objects).unwrap();
+        std::fs::write(objects.join("hangar.obj"), "").unwrap();
+        std::fs::write(objects.join("hangar.png"), "").unwrap();
+        std::os::unix::fs::symlink(pack_dir.path(), objects.join("loop")).unwrap();
+
+        assert_eq!(
+            vec![PathBuf::from("objects/hangar.obj")],
+            find_art_assets(pack_dir.path()).unwrap()
+        );
+    }
+}
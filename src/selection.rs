@@ -0,0 +1,229 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use crate::parsers::scenery_packs::SceneryPack;
+
+/// The scenery packs which are selected in the list, identified by
+/// [SceneryPack::path], so that the selection is retained when the
+/// scenery packs are reordered.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    selected: HashSet<PathBuf>,
+    /// The scenery pack which was most recently clicked, which is the
+    /// start of the range when shift-clicking.
+    anchor: Option<PathBuf>,
+}
+
+impl Selection {
+    pub fn is_selected(&self, path: &Path) -> bool {
+        self.selected.contains(path)
+    }
+
+    /// The indices of the selected scenery packs in `scenery_packs`, in
+    /// ascending order.
+    pub fn indices(&self, scenery_packs: &im_rc::Vector<SceneryPack>) -> Vec<usize> {
+        scenery_packs
+            .iter()
+            .enumerate()
+            .filter(|(_, scenery_pack)| self.is_selected(&scenery_pack.path))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Select only the scenery pack at `index` (a click).
+    pub fn select_only(&mut self, scenery_packs: &im_rc::Vector<SceneryPack>, index: usize) {
+        self.selected.clear();
+        self.toggle(scenery_packs, index);
+    }
+
+    /// Toggle whether the scenery pack at `index` is selected (a
+    /// ctrl-click).
+    pub fn toggle(&mut self, scenery_packs: &im_rc::Vector<SceneryPack>, index: usize) {
+        if let Some(scenery_pack) = scenery_packs.get(index) {
+            if !self.selected.remove(&scenery_pack.path) {
+                self.selected.insert(scenery_pack.path.clone());
+            }
+            self.anchor = Some(scenery_pack.path.clone());
+        }
+    }
+
+    /// Select the scenery packs between the anchor (the most recently
+    /// clicked scenery pack) and `index` inclusive (a shift-click).
+    pub fn select_range(&mut self, scenery_packs: &im_rc::Vector<SceneryPack>, index: usize) {
+        let anchor_index = self.anchor.as_ref().and_then(|anchor| {
+            scenery_packs
+                .iter()
+                .position(|scenery_pack| &scenery_pack.path == anchor)
+        });
+        let anchor_index = match anchor_index {
+            Some(anchor_index) => anchor_index,
+            None => return self.select_only(scenery_packs, index),
+        };
+
+        let range = anchor_index.min(index)..=anchor_index.max(index);
+        self.selected.clear();
+        self.selected.extend(
+            scenery_packs
+                .iter()
+                .skip(*range.start())
+                .take(range.count())
+                .map(|scenery_pack| scenery_pack.path.clone()),
+        );
+    }
+
+    pub fn select_all(&mut self, scenery_packs: &im_rc::Vector<SceneryPack>) {
+        self.selected = scenery_packs
+            .iter()
+            .map(|scenery_pack| scenery_pack.path.clone())
+            .collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.selected.clear();
+        self.anchor = None;
+    }
+
+    /// Move the selected scenery packs, keeping their relative order,
+    /// so that they are placed before the scenery pack which is
+    /// currently at `index`, or at the end if `index` is the number of
+    /// scenery packs.
+    pub fn move_to_index(
+        &self,
+        scenery_packs: &im_rc::Vector<SceneryPack>,
+        index: usize,
+    ) -> im_rc::Vector<SceneryPack> {
+        let (selected, mut others): (im_rc::Vector<_>, im_rc::Vector<_>) = scenery_packs
+            .iter()
+            .cloned()
+            .partition(|scenery_pack| self.is_selected(&scenery_pack.path));
+        let insert_index = scenery_packs
+            .iter()
+            .take(index)
+            .filter(|scenery_pack| !self.is_selected(&scenery_pack.path))
+            .count();
+        let tail = others.split_off(insert_index);
+        others + selected + tail
+    }
+
+    /// Move each of the selected scenery packs `offset` places up
+    /// (negative) or down (positive) the list. Scenery packs stop at
+    /// the start or end of the list, and selected scenery packs never
+    /// pass each other, so their relative order is retained.
+    pub fn move_by_offset(
+        &self,
+        scenery_packs: &im_rc::Vector<SceneryPack>,
+        offset: isize,
+    ) -> im_rc::Vector<SceneryPack> {
+        let mut scenery_packs = scenery_packs.clone();
+        let len = scenery_packs.len();
+        for _ in 0..offset.unsigned_abs() {
+            // Move the selected scenery packs one place at a time,
+            // starting with the one nearest to the direction of travel.
+            let indices: Box<dyn Iterator<Item = usize>> = if offset < 0 {
+                Box::new(1..len)
+            } else {
+                Box::new((0..len.saturating_sub(1)).rev())
+            };
+            for index in indices {
+                let other = if offset < 0 { index - 1 } else { index + 1 };
+                if self.is_selected(&scenery_packs[index].path)
+                    && !self.is_selected(&scenery_packs[other].path)
+                {
+                    scenery_packs.swap(index, other);
+                }
+            }
+        }
+        scenery_packs
+    }
+
+    pub fn invert(&mut self, scenery_packs: &im_rc::Vector<SceneryPack>) {
+        self.selected = scenery_packs
+            .iter()
+            .map(|scenery_pack| &scenery_pack.path)
+            .filter(|path| !self.selected.contains(*path))
+            .cloned()
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::Selection;
+    use crate::parsers::scenery_packs::SceneryPack;
+
+    fn example_scenery_packs() -> im_rc::Vector<SceneryPack> {
+        (0..5)
+            .map(|index| SceneryPack::new(&format!("Custom Scenery/Pack {}/", index), true))
+            .collect()
+    }
+
+    #[test]
+    fn test_selection() {
+        let scenery_packs = example_scenery_packs();
+        let mut selection = Selection::default();
+
+        selection.select_only(&scenery_packs, 1);
+        assert_eq!(vec![1], selection.indices(&scenery_packs));
+
+        selection.select_range(&scenery_packs, 3);
+        assert_eq!(vec![1, 2, 3], selection.indices(&scenery_packs));
+
+        selection.toggle(&scenery_packs, 2);
+        assert_eq!(vec![1, 3], selection.indices(&scenery_packs));
+
+        // The range starts from the most recently clicked scenery pack.
+        selection.select_range(&scenery_packs, 0);
+        assert_eq!(vec![0, 1, 2], selection.indices(&scenery_packs));
+
+        selection.invert(&scenery_packs);
+        assert_eq!(vec![3, 4], selection.indices(&scenery_packs));
+
+        selection.select_all(&scenery_packs);
+        assert_eq!(vec![0, 1, 2, 3, 4], selection.indices(&scenery_packs));
+
+        selection.clear();
+        assert!(selection.indices(&scenery_packs).is_empty());
+    }
+
+    fn pack_numbers(scenery_packs: &im_rc::Vector<SceneryPack>) -> Vec<String> {
+        scenery_packs
+            .iter()
+            .map(|scenery_pack| {
+                scenery_pack
+                    .path
+                    .to_string_lossy()
+                    .trim_start_matches("Custom Scenery/Pack ")
+                    .trim_end_matches('/')
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_move() {
+        let scenery_packs = example_scenery_packs();
+        let mut selection = Selection::default();
+        selection.select_only(&scenery_packs, 1);
+        selection.toggle(&scenery_packs, 3);
+
+        let moved = selection.move_to_index(&scenery_packs, 0);
+        assert_eq!(vec!["1", "3", "0", "2", "4"], pack_numbers(&moved));
+        let moved = selection.move_to_index(&scenery_packs, 5);
+        assert_eq!(vec!["0", "2", "4", "1", "3"], pack_numbers(&moved));
+        let moved = selection.move_to_index(&scenery_packs, 3);
+        assert_eq!(vec!["0", "2", "1", "3", "4"], pack_numbers(&moved));
+
+        let moved = selection.move_by_offset(&scenery_packs, -1);
+        assert_eq!(vec!["1", "0", "3", "2", "4"], pack_numbers(&moved));
+        let moved = selection.move_by_offset(&scenery_packs, -2);
+        assert_eq!(vec!["1", "3", "0", "2", "4"], pack_numbers(&moved));
+        let moved = selection.move_by_offset(&scenery_packs, 1);
+        assert_eq!(vec!["0", "2", "1", "4", "3"], pack_numbers(&moved));
+        let moved = selection.move_by_offset(&scenery_packs, 10);
+        assert_eq!(vec!["0", "2", "4", "1", "3"], pack_numbers(&moved));
+    }
+}
@@ -1,75 +1,53 @@
 use directories::ProjectDirs;
 use eyre::Context;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use std::path::PathBuf;
 
-use crate::view::page::setup::SetupParameters;
+use crate::{classify::SortRule, view::page::setup::SetupParameters};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Settings {
     pub xplane_dir: Option<PathBuf>,
+    /// The rules used to sort the load order of scenery packs. See
+    /// [sort_scenery_packs()](crate::classify::sort_scenery_packs).
+    #[serde(default = "default_sort_rules")]
+    pub sort_rules: Vec<SortRule>,
+    /// The number of backups of `scenery_packs.ini` to keep. See
+    /// [Backups](crate::backup::Backups).
+    #[serde(default = "default_backup_count")]
+    pub backup_count: usize,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        Settings { xplane_dir: None }
+        Self {
+            xplane_dir: None,
+            sort_rules: default_sort_rules(),
+            backup_count: default_backup_count(),
+        }
     }
 }
 
+fn default_sort_rules() -> Vec<SortRule> {
+    SortRule::DEFAULT.to_vec()
+}
+
+fn default_backup_count() -> usize {
+    10
+}
+
 impl Settings {
     pub fn setup(&mut self, parameters: SetupParameters) {
         self.xplane_dir = Some(parameters.xplane_dir);
     }
 
     pub fn from_settings_file() -> eyre::Result<Option<Self>> {
-        let settings_path = Self::settings_path()?;
-
-        if !settings_path.exists() {
-            tracing::debug!("settings file {:?} does not yet exist", &settings_path);
-            return Ok(None);
-        } else {
-            tracing::debug!("Reading settings from {:?}", &settings_path);
-            let file = std::fs::File::open(&settings_path).wrap_err_with(|| {
-                eyre::eyre!("Unable to open settings file {:?}", &settings_path)
-            })?;
-            let settings: Self = ron::de::from_reader(file).wrap_err_with(|| {
-                eyre::eyre!(
-                    "Error while deserializing settings file {:?}",
-                    &settings_path
-                )
-            })?;
-            return Ok(Some(settings));
-        }
+        read_config_file("settings.ron")
     }
 
     pub fn save(&self) -> eyre::Result<()> {
-        let settings_path = Self::settings_path()?;
-        tracing::debug!("Writing settings to {:?}", &settings_path);
-        let parent = settings_path.parent().ok_or_else(|| {
-            eyre::eyre!(
-                "Settings file does not have a parent directory: {:?}",
-                &settings_path
-            )
-        })?;
-
-        if !parent.exists() {
-            std::fs::create_dir(parent)?;
-        }
-
-        let file = std::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(settings_path)?;
-
-        ron::ser::to_writer_pretty(file, self, Default::default())
-            .wrap_err("Error while serializing settings")?;
-
-        Ok(())
-    }
-
-    fn settings_path() -> eyre::Result<PathBuf> {
-        Ok(Self::project_dirs()?.config_dir().join("settings.ron"))
+        write_config_file("settings.ron", self)
     }
 
     pub fn project_dirs() -> eyre::Result<ProjectDirs> {
@@ -78,3 +56,52 @@ impl Settings {
         })
     }
 }
+
+/// Read a `ron` file from the application's configuration directory,
+/// alongside the [Settings] file. Returns `None` if the file does not
+/// yet exist.
+pub fn read_config_file<T: DeserializeOwned>(file_name: &str) -> eyre::Result<Option<T>> {
+    let path = Settings::project_dirs()?.config_dir().join(file_name);
+
+    if !path.exists() {
+        tracing::debug!("{} file {:?} does not yet exist", file_name, &path);
+        Ok(None)
+    } else {
+        tracing::debug!("Reading {} from {:?}", file_name, &path);
+        let file = std::fs::File::open(&path)
+            .wrap_err_with(|| eyre::eyre!("Unable to open {} file {:?}", file_name, &path))?;
+        let value: T = ron::de::from_reader(file).wrap_err_with(|| {
+            eyre::eyre!("Error while deserializing {} file {:?}", file_name, &path)
+        })?;
+        Ok(Some(value))
+    }
+}
+
+/// Write a `ron` file to the application's configuration directory,
+/// creating the directory if it does not yet exist.
+pub fn write_config_file<T: Serialize>(file_name: &str, value: &T) -> eyre::Result<()> {
+    let path = Settings::project_dirs()?.config_dir().join(file_name);
+    tracing::debug!("Writing {} to {:?}", file_name, &path);
+    let parent = path.parent().ok_or_else(|| {
+        eyre::eyre!(
+            "{} file does not have a parent directory: {:?}",
+            file_name,
+            &path
+        )
+    })?;
+
+    if !parent.exists() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+
+    ron::ser::to_writer_pretty(file, value, Default::default())
+        .wrap_err_with(|| eyre::eyre!("Error while serializing {}", file_name))?;
+
+    Ok(())
+}
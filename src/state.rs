@@ -1,10 +1,23 @@
-use std::rc::Rc;
+use std::{collections::HashSet, path::PathBuf, rc::Rc};
 
 use reactive_state::{Reducer, ReducerResult, StoreRef};
 
 use crate::{
-    history::History, i18n::LocalizedString, parsers::scenery_packs::SceneryPack,
+    airports::{disable_losers, prefer_provider, AirportIndex},
+    classify::{insert_position, sort_scenery_packs, SceneryCategory},
+    dependencies::DependencyGraph,
+    diff::SceneryPacksDiff,
+    fl,
+    geo::GeoArea,
+    history::History,
+    i18n::LocalizedString,
+    parsers::scenery_packs::{SceneryPack, SceneryPackPath},
+    presets::{Preset, Presets},
+    scan::SceneryScan,
+    selection::Selection,
     settings::Settings,
+    tags::Tags,
+    xplane::XPlaneVersion,
 };
 
 #[derive(Clone)]
@@ -16,7 +29,7 @@ pub struct ActionHistoryItem<T> {
 impl<T: Default> ActionHistoryItem<T> {
     fn with_empty_label(item: T) -> Self {
         Self {
-            label: LocalizedString::new(|| String::new()),
+            label: LocalizedString::new(String::new),
             item,
         }
     }
@@ -39,18 +52,10 @@ impl<T: Default> Default for ActionHistoryItem<T> {
 #[derive(Clone, Default)]
 pub struct SceneryPacksHistoryItem {
     pub scenery_packs: im_rc::Vector<SceneryPack>,
+    pub tags: Rc<Tags>,
     pub id: u64,
 }
 
-impl From<im_rc::Vector<SceneryPack>> for SceneryPacksHistoryItem {
-    fn from(scenery_packs: im_rc::Vector<SceneryPack>) -> Self {
-        Self {
-            scenery_packs,
-            id: rand::random(),
-        }
-    }
-}
-
 #[derive(Clone, Default)]
 pub struct ScenableState {
     pub settings: Rc<Settings>,
@@ -59,6 +64,28 @@ pub struct ScenableState {
     /// The id of the [SceneryPacksHistoryItem] which corresponds to
     /// the currently saved state of the `scenery_packs.ini` file.
     pub scenery_packs_saved_history_id: u64,
+    /// The scenery packs as they are currently saved in the
+    /// `scenery_packs.ini` file.
+    pub saved_scenery_packs: im_rc::Vector<SceneryPack>,
+    /// The most recent scan of the scenery packs on disk.
+    pub scenery_scan: Rc<SceneryScan>,
+    /// Dependencies between scenery packs, calculated from
+    /// `scenery_scan`.
+    pub dependencies: Rc<DependencyGraph>,
+    /// The airports provided by scenery packs, calculated from
+    /// `scenery_scan`.
+    pub airports: Rc<AirportIndex>,
+    /// The user's saved presets.
+    pub presets: Rc<Presets>,
+    /// The user's tags for scenery packs. These are recorded in the
+    /// history along with the scenery packs, and saved along with
+    /// `scenery_packs.ini`.
+    pub tags: Rc<Tags>,
+    /// The scenery packs which are selected in the list.
+    pub selection: Rc<Selection>,
+    /// The version of the X-Plane installation in
+    /// [Settings::xplane_dir], if it could be detected.
+    pub xplane_version: Option<XPlaneVersion>,
 }
 
 impl std::fmt::Debug for ScenableState {
@@ -69,6 +96,23 @@ impl std::fmt::Debug for ScenableState {
 }
 
 impl ScenableState {
+    /// Create a history item from the current scenery packs and
+    /// tags.
+    fn scenery_packs_history_item(&self) -> SceneryPacksHistoryItem {
+        SceneryPacksHistoryItem {
+            scenery_packs: self.scenery_packs.clone(),
+            tags: self.tags.clone(),
+            id: rand::random(),
+        }
+    }
+
+    /// Record the current scenery packs and tags in the history.
+    fn push_scenery_packs_history(&mut self, label: LocalizedString) {
+        let item = self.scenery_packs_history_item();
+        self.scenery_packs_history
+            .push(ActionHistoryItem { label, item });
+    }
+
     fn senery_packs_current_history_id(&self) -> u64 {
         let (current_history, _) = self.scenery_packs_history.peek_current();
         current_history.item.id
@@ -78,10 +122,75 @@ impl ScenableState {
     pub fn scenery_packs_synchronized(&self) -> bool {
         self.scenery_packs_saved_history_id == self.senery_packs_current_history_id()
     }
+
+    /// The differences between the scenery packs saved in
+    /// `scenery_packs.ini` and the current scenery packs.
+    pub fn scenery_packs_diff(&self) -> SceneryPacksDiff {
+        SceneryPacksDiff::new(&self.saved_scenery_packs, &self.scenery_packs)
+    }
+
+    /// Whether the scenery packs are missing the `*GLOBAL_AIRPORTS*`
+    /// entry, which the installed version of X-Plane requires (see
+    /// [XPlaneVersion::requires_global_airports_entry()]).
+    pub fn missing_global_airports(&self) -> bool {
+        let requires_global_airports = self
+            .xplane_version
+            .map(|version| version.requires_global_airports_entry())
+            .unwrap_or(false);
+        requires_global_airports
+            && !self
+                .scenery_packs
+                .iter()
+                .any(|scenery_pack| scenery_pack.location() == SceneryPackPath::GlobalAirports)
+    }
+
+    /// Scenery packs which are installed in `Custom Scenery`, but are
+    /// not listed in the scenery packs, according to the most recent
+    /// scan. Scenery packs are compared by their directory, so that
+    /// e.g. `Custom Scenery/Global Airports/` is not new when
+    /// `*GLOBAL_AIRPORTS*` is listed.
+    pub fn new_scenery_packs(&self) -> Vec<PathBuf> {
+        let installed = match &self.scenery_scan.installed {
+            Some(installed) => installed,
+            None => return Vec::new(),
+        };
+        let mut listed_dirs: HashSet<&PathBuf> = self
+            .scenery_packs
+            .iter()
+            .map(|scenery_pack| {
+                installed
+                    .get(&scenery_pack.path)
+                    .unwrap_or(&scenery_pack.path)
+            })
+            .collect();
+        installed
+            .iter()
+            .filter(|(_, dir)| listed_dirs.insert(dir))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Scenery packs which are listed in the scenery packs, but are no
+    /// longer installed, according to the most recent scan.
+    pub fn removed_scenery_packs(&self) -> Vec<PathBuf> {
+        let installed = match &self.scenery_scan.installed {
+            Some(installed) => installed,
+            None => return Vec::new(),
+        };
+        self.scenery_packs
+            .iter()
+            // Special entries such as `*SOMETHING*` are not directories.
+            .filter(|scenery_pack| !matches!(scenery_pack.location(), SceneryPackPath::Special(_)))
+            .map(|scenery_pack| &scenery_pack.path)
+            .filter(|path| !installed.contains_key(*path))
+            .cloned()
+            .collect()
+    }
 }
 
 pub enum ActionHistory {
     Some(LocalizedString),
+    #[allow(dead_code)]
     None,
 }
 
@@ -119,13 +228,179 @@ pub struct UpdateSceneryPack {
     pub history: ActionHistory,
 }
 
+/// Enable or disable a single scenery pack, along with the libraries
+/// that it requires. See [DependencyGraph::set_enabled()]. The change
+/// is recorded as a single history item.
+#[derive(Debug)]
+pub struct EnableSceneryPack {
+    pub index: usize,
+    pub enabled: bool,
+}
+
+/// Enable only the scenery packs whose DSF tiles or airports intersect
+/// `area`, and disable the other scenery packs which have a known
+/// location. Libraries are enabled and disabled as required (see
+/// [DependencyGraph::set_enabled_many()]), and scenery packs without
+/// a known location are otherwise left untouched. The change is
+/// recorded as a single history item.
+#[derive(Debug)]
+pub struct EnableArea {
+    pub area: GeoArea,
+}
+
+/// Add new scenery packs (see [ScenableState::new_scenery_packs()]),
+/// enabled, each at the position conventional for its categories (see
+/// [insert_position()]). The change is recorded as a single history
+/// item.
+#[derive(Debug)]
+pub struct AddSceneryPacks {
+    pub paths: Vec<PathBuf>,
+}
+
+/// Remove scenery packs, usually those which are no longer installed
+/// (see [ScenableState::removed_scenery_packs()]). The change is
+/// recorded as a single history item.
+#[derive(Debug)]
+pub struct RemoveSceneryPacks {
+    pub paths: Vec<PathBuf>,
+}
+
+/// Adopt the scenery packs in `scenery_packs.ini` after it was
+/// modified by another program, along with the result of merging them
+/// with the current scenery packs (see [merge()](crate::merge::merge)).
+/// `on_disk` becomes the saved state, and `merged` the current state,
+/// recorded as a single history item.
+#[derive(Debug)]
+pub struct MergeSceneryPacks {
+    pub on_disk: im_rc::Vector<SceneryPack>,
+    pub merged: im_rc::Vector<SceneryPack>,
+}
+
+/// Enable or disable the scenery packs which provide the airport
+/// `ident` (see
+/// [AirportEntry::enable_changes()](crate::airports::AirportEntry::enable_changes)), along with the
+/// libraries that they require (see
+/// [DependencyGraph::set_enabled_many()]). The change is recorded as a
+/// single history item.
+#[derive(Debug)]
+pub struct EnableAirport {
+    pub ident: String,
+    pub enabled: bool,
+}
+
+/// Resolve [AirportConflict](crate::airports::AirportConflict)s
+/// between the enabled scenery packs (see [AirportIndex::conflicts()]).
+/// The change is recorded as a single history item.
+#[derive(Debug)]
+pub enum ResolveAirportConflicts {
+    /// Disable the scenery packs which lose the conflicts over the
+    /// airports with these identifiers (see [disable_losers()]).
+    DisableLosers { idents: Vec<String> },
+    /// Move the scenery pack at `path` above the scenery pack which
+    /// wins the conflict over the airport `ident`, so that its airport
+    /// is used instead (see [prefer_provider()]).
+    Prefer { ident: String, path: PathBuf },
+}
+
+/// Change which scenery packs are selected (see [Selection]).
+#[derive(Debug)]
+pub enum SelectSceneryPacks {
+    /// Select only the scenery pack at `index` (a click).
+    Only {
+        index: usize,
+    },
+    /// Select the range of scenery packs from the most recently
+    /// clicked one to `index` (a shift-click).
+    Range {
+        index: usize,
+    },
+    /// Toggle whether the scenery pack at `index` is selected (a
+    /// ctrl-click).
+    Toggle {
+        index: usize,
+    },
+    All,
+    None,
+    Invert,
+}
+
+/// Enable or disable all the selected scenery packs. If
+/// `manage_libraries` is set, the libraries that they require are
+/// enabled or disabled as well (see
+/// [DependencyGraph::set_enabled_many()]). The change is recorded as
+/// a single history item.
+#[derive(Debug)]
+pub struct EnableSelected {
+    pub enabled: bool,
+    pub manage_libraries: bool,
+}
+
+/// Move the selected scenery packs, keeping their relative order. The
+/// change is recorded as a single history item.
+#[derive(Debug)]
+pub enum MoveSelected {
+    /// See [Selection::move_to_index()].
+    ToIndex(usize),
+    /// See [Selection::move_by_offset()].
+    ByOffset(isize),
+}
+
+/// Add or remove a tag on all the selected scenery packs. The change
+/// is recorded as a single history item.
+#[derive(Debug)]
+pub struct TagSelected {
+    pub tag: String,
+    pub add: bool,
+}
+
+/// Create, apply or edit a [Preset].
+#[derive(Debug)]
+pub enum PresetAction {
+    /// Create a new preset from the current state of the scenery
+    /// packs.
+    Create {
+        name: String,
+    },
+    /// Apply the preset to the scenery packs (see [Preset::apply()]).
+    /// This is recorded as a single history item, in the same manner
+    /// as [UpdateSceneryPacks].
+    Apply {
+        name: String,
+    },
+    /// Replace the contents of the preset with the current state of
+    /// the scenery packs.
+    Update {
+        name: String,
+    },
+    Rename {
+        name: String,
+        new_name: String,
+    },
+    Delete {
+        name: String,
+    },
+    Duplicate {
+        name: String,
+        new_name: String,
+    },
+    /// Add the selected scenery packs to the preset, with their
+    /// current enabled state (see [Presets::add_scenery_packs()]).
+    AddSelected {
+        name: String,
+    },
+}
+
 pub enum ScenableAction {
     /// Update the application [Settings].
     UpdateSettings(Settings),
+    /// Update the detected version of X-Plane.
+    UpdateXPlaneVersion(Option<XPlaneVersion>),
     /// See [UpdateSceneryPacks].
     UpdateSceneryPacks(UpdateSceneryPacks),
     /// See [UpdateSceneryPack].
     UpdateSceneryPack(UpdateSceneryPack),
+    /// See [EnableSceneryPack].
+    EnableSceneryPack(EnableSceneryPack),
     /// Undo previous change to scenery packs.
     UndoSceneryPacks,
     /// Redo previous change to scenery packs.
@@ -133,23 +408,105 @@ pub enum ScenableAction {
     /// Notifies that the state of scenery packs has been read from or
     /// written to disk.
     UpdateSceneryPacksSyncStatus,
+    /// See [MergeSceneryPacks].
+    MergeSceneryPacks(MergeSceneryPacks),
+    /// Replace the [SceneryScan] with the result of a new scan, and
+    /// recalculate the [DependencyGraph].
+    UpdateSceneryScan(Rc<SceneryScan>),
+    /// See [EnableArea].
+    EnableArea(EnableArea),
+    /// See [AddSceneryPacks].
+    AddSceneryPacks(AddSceneryPacks),
+    /// See [RemoveSceneryPacks].
+    RemoveSceneryPacks(RemoveSceneryPacks),
+    /// Sort the scenery packs into the load order given by
+    /// [Settings::sort_rules] (see [sort_scenery_packs()]). The change
+    /// is recorded as a single history item.
+    SortSceneryPacks,
+    /// See [EnableAirport].
+    EnableAirport(EnableAirport),
+    /// See [ResolveAirportConflicts].
+    ResolveAirportConflicts(ResolveAirportConflicts),
+    /// See [SelectSceneryPacks].
+    SelectSceneryPacks(SelectSceneryPacks),
+    /// See [EnableSelected].
+    EnableSelected(EnableSelected),
+    /// See [MoveSelected].
+    MoveSelected(MoveSelected),
+    /// See [TagSelected].
+    TagSelected(TagSelected),
+    /// See [PresetAction].
+    Preset(PresetAction),
 }
 
 impl std::fmt::Debug for ScenableAction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ScenableAction::UpdateSettings(_) => f.debug_tuple("UpdateSettings").finish(),
+            ScenableAction::UpdateXPlaneVersion(version) => {
+                f.debug_tuple("UpdateXPlaneVersion").field(version).finish()
+            }
             ScenableAction::UpdateSceneryPacks(_) => f.debug_tuple("UpdateScenery").finish(),
             ScenableAction::UpdateSceneryPack(action) => f
                 .debug_tuple("UpdateScenery")
                 .field(&action.index)
                 .field(&action.history.format_label())
                 .finish(),
+            ScenableAction::EnableSceneryPack(action) => f
+                .debug_tuple("EnableSceneryPack")
+                .field(&action.index)
+                .field(&action.enabled)
+                .finish(),
             ScenableAction::UndoSceneryPacks => f.debug_tuple("UndoSceneryPacks").finish(),
             ScenableAction::RedoSceneryPacks => f.debug_tuple("RedoSceneryPacks").finish(),
             ScenableAction::UpdateSceneryPacksSyncStatus => {
                 f.debug_tuple("UpdateSceneryPacksSyncStatus").finish()
             }
+            ScenableAction::MergeSceneryPacks(action) => f
+                .debug_tuple("MergeSceneryPacks")
+                .field(&action.merged.len())
+                .finish(),
+            ScenableAction::UpdateSceneryScan(scan) => f
+                .debug_tuple("UpdateSceneryScan")
+                .field(&scan.packs.len())
+                .finish(),
+            ScenableAction::EnableArea(action) => {
+                f.debug_tuple("EnableArea").field(&action.area).finish()
+            }
+            ScenableAction::AddSceneryPacks(action) => f
+                .debug_tuple("AddSceneryPacks")
+                .field(&action.paths)
+                .finish(),
+            ScenableAction::RemoveSceneryPacks(action) => f
+                .debug_tuple("RemoveSceneryPacks")
+                .field(&action.paths)
+                .finish(),
+            ScenableAction::SortSceneryPacks => f.debug_tuple("SortSceneryPacks").finish(),
+            ScenableAction::EnableAirport(action) => f
+                .debug_tuple("EnableAirport")
+                .field(&action.ident)
+                .field(&action.enabled)
+                .finish(),
+            ScenableAction::ResolveAirportConflicts(action) => f
+                .debug_tuple("ResolveAirportConflicts")
+                .field(action)
+                .finish(),
+            ScenableAction::SelectSceneryPacks(action) => {
+                f.debug_tuple("SelectSceneryPacks").field(action).finish()
+            }
+            ScenableAction::EnableSelected(action) => f
+                .debug_tuple("EnableSelected")
+                .field(&action.enabled)
+                .finish(),
+            ScenableAction::MoveSelected(action) => {
+                f.debug_tuple("MoveSelected").field(action).finish()
+            }
+            ScenableAction::TagSelected(action) => f
+                .debug_tuple("TagSelected")
+                .field(&action.tag)
+                .field(&action.add)
+                .finish(),
+            ScenableAction::Preset(action) => f.debug_tuple("Preset").field(action).finish(),
         }
     }
 }
@@ -176,24 +533,17 @@ impl Reducer<ScenableState, ScenableAction, (), ()> for ScenableReducer {
                     effects: vec![],
                 }
             }
-            ScenableAction::UpdateSceneryPacks(action) => {
-                match &action.history {
-                    ActionHistory::Some(label) => {
-                        let history_item = ActionHistoryItem {
-                            label: label.clone(),
-                            item: From::from(action.scenery_packs.clone()),
-                        };
+            ScenableAction::UpdateXPlaneVersion(version) => {
+                new_state.xplane_version = *version;
 
-                        if action.reset_history {
-                            new_state.scenery_packs_history.reset(history_item);
-                        } else {
-                            new_state.scenery_packs_history.push(history_item);
-                        }
-                    }
-                    ActionHistory::None => {}
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
                 }
-
-                new_state.scenery_packs = action.scenery_packs.clone();
+            }
+            ScenableAction::UpdateSceneryPacks(action) => {
+                update_scenery_packs(&mut new_state, action);
 
                 ReducerResult {
                     state: Rc::new(new_state),
@@ -208,14 +558,31 @@ impl Reducer<ScenableState, ScenableAction, (), ()> for ScenableReducer {
                     tracing::error!("No scenery pack exists for the specified index")
                 }
 
-                match &action.history {
-                    ActionHistory::Some(label) => {
-                        new_state.scenery_packs_history.push(ActionHistoryItem {
-                            label: label.clone(),
-                            item: From::from(new_state.scenery_packs.clone()),
-                        });
-                    }
-                    ActionHistory::None => {}
+                if let ActionHistory::Some(label) = &action.history {
+                    new_state.push_scenery_packs_history(label.clone());
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::EnableSceneryPack(action) => {
+                let scenery_packs = new_state.dependencies.set_enabled(
+                    &new_state.scenery_packs,
+                    action.index,
+                    action.enabled,
+                );
+
+                if scenery_packs != new_state.scenery_packs {
+                    let label = enable_scenery_pack_label(
+                        &new_state.scenery_packs,
+                        &scenery_packs,
+                        action.index,
+                    );
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(label);
                 }
 
                 ReducerResult {
@@ -227,6 +594,7 @@ impl Reducer<ScenableState, ScenableAction, (), ()> for ScenableReducer {
             ScenableAction::UndoSceneryPacks => {
                 if let Some((history_item, _)) = new_state.scenery_packs_history.undo() {
                     new_state.scenery_packs = history_item.item.scenery_packs.clone();
+                    new_state.tags = history_item.item.tags.clone();
                 } else {
                     tracing::warn!("Can't undo, history is either empty or this is the first item");
                 }
@@ -239,6 +607,7 @@ impl Reducer<ScenableState, ScenableAction, (), ()> for ScenableReducer {
             ScenableAction::RedoSceneryPacks => {
                 if let Some((history_item, _)) = new_state.scenery_packs_history.redo() {
                     new_state.scenery_packs = history_item.item.scenery_packs.clone();
+                    new_state.tags = history_item.item.tags.clone();
                 } else {
                     tracing::warn!("Can't redo, history is either empty or this is the first item");
                 }
@@ -251,12 +620,534 @@ impl Reducer<ScenableState, ScenableAction, (), ()> for ScenableReducer {
             ScenableAction::UpdateSceneryPacksSyncStatus => {
                 new_state.scenery_packs_saved_history_id =
                     new_state.senery_packs_current_history_id();
+                new_state.saved_scenery_packs = new_state.scenery_packs.clone();
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::MergeSceneryPacks(action) => {
+                new_state.saved_scenery_packs = action.on_disk.clone();
+                new_state.scenery_packs = action.merged.clone();
+                new_state.push_scenery_packs_history(LocalizedString::new(|| {
+                    fl!("scenery-packs-merged-operation")
+                }));
+                // No history item corresponds to the file on disk,
+                // unless the merge made no changes to it.
+                new_state.scenery_packs_saved_history_id = if action.merged == action.on_disk {
+                    new_state.senery_packs_current_history_id()
+                } else {
+                    0
+                };
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::UpdateSceneryScan(scan) => {
+                new_state.dependencies = Rc::new(DependencyGraph::new(scan));
+                new_state.airports = Rc::new(AirportIndex::new(scan));
+                new_state.scenery_scan = scan.clone();
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::EnableArea(action) => {
+                let changes: Vec<(usize, bool)> = new_state
+                    .scenery_packs
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, scenery_pack)| {
+                        let pack_scan = new_state.scenery_scan.packs.get(&scenery_pack.path)?;
+                        Some((index, pack_scan.intersects(&action.area)?))
+                    })
+                    .collect();
+                let scenery_packs = new_state
+                    .dependencies
+                    .set_enabled_many(&new_state.scenery_packs, &changes);
+
+                if scenery_packs != new_state.scenery_packs {
+                    let label = enable_area_label(&new_state.scenery_packs, &scenery_packs);
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(label);
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::AddSceneryPacks(action) => {
+                let mut scenery_packs = new_state.scenery_packs.clone();
+                let mut count = 0;
+                for path in &action.paths {
+                    // Scenery packs which are already listed are not
+                    // added again.
+                    if scenery_packs
+                        .iter()
+                        .any(|scenery_pack| &scenery_pack.path == path)
+                    {
+                        continue;
+                    }
+                    // The global airports are loaded after the custom
+                    // airports, whether or not they have been scanned.
+                    let categories =
+                        if SceneryPackPath::new(path) == SceneryPackPath::GlobalAirports {
+                            std::iter::once(SceneryCategory::Airport).collect()
+                        } else {
+                            new_state
+                                .scenery_scan
+                                .packs
+                                .get(path)
+                                .map(|pack_scan| pack_scan.categories.clone())
+                                .unwrap_or_default()
+                        };
+                    let index =
+                        insert_position(&scenery_packs, &new_state.scenery_scan, &categories);
+                    scenery_packs.insert(
+                        index,
+                        SceneryPack {
+                            enabled: true,
+                            path: path.clone(),
+                        },
+                    );
+                    count += 1;
+                }
+
+                if count > 0 {
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(LocalizedString::new(move || {
+                        fl!("scenery-packs-added-operation", count = count)
+                    }));
+                }
+
                 ReducerResult {
                     state: Rc::new(new_state),
                     events: vec![],
                     effects: vec![],
                 }
             }
+            ScenableAction::RemoveSceneryPacks(action) => {
+                let paths: HashSet<&PathBuf> = action.paths.iter().collect();
+                let scenery_packs: im_rc::Vector<SceneryPack> = new_state
+                    .scenery_packs
+                    .iter()
+                    .filter(|scenery_pack| !paths.contains(&scenery_pack.path))
+                    .cloned()
+                    .collect();
+
+                let count = new_state.scenery_packs.len() - scenery_packs.len();
+                if count > 0 {
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(LocalizedString::new(move || {
+                        fl!("scenery-packs-removed-operation", count = count)
+                    }));
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::SortSceneryPacks => {
+                let scenery_packs = sort_scenery_packs(
+                    &new_state.scenery_packs,
+                    &new_state.scenery_scan,
+                    &new_state.settings.sort_rules,
+                );
+
+                if scenery_packs != new_state.scenery_packs {
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(LocalizedString::new(|| {
+                        fl!("scenery-packs-sorted-operation")
+                    }));
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::EnableAirport(action) => {
+                let changes = new_state
+                    .airports
+                    .get(&action.ident)
+                    .map(|airport| airport.enable_changes(&new_state.scenery_packs, action.enabled))
+                    .unwrap_or_default();
+                let scenery_packs = new_state
+                    .dependencies
+                    .set_enabled_many(&new_state.scenery_packs, &changes);
+
+                if scenery_packs != new_state.scenery_packs {
+                    let (ident, enabled) = (action.ident.clone(), action.enabled);
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(LocalizedString::new(move || {
+                        if enabled {
+                            fl!("airport-enabled-operation", ident = ident.clone())
+                        } else {
+                            fl!("airport-disabled-operation", ident = ident.clone())
+                        }
+                    }));
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::ResolveAirportConflicts(action) => {
+                let conflicts = new_state.airports.conflicts(&new_state.scenery_packs);
+                let scenery_packs = match action {
+                    ResolveAirportConflicts::DisableLosers { idents } => {
+                        let conflicts: Vec<_> = conflicts
+                            .into_iter()
+                            .filter(|conflict| idents.contains(&conflict.ident))
+                            .collect();
+                        disable_losers(&new_state.scenery_packs, &conflicts)
+                    }
+                    ResolveAirportConflicts::Prefer { ident, path } => {
+                        match conflicts.iter().find(|conflict| &conflict.ident == ident) {
+                            Some(conflict) => {
+                                prefer_provider(&new_state.scenery_packs, conflict, path)
+                            }
+                            None => new_state.scenery_packs.clone(),
+                        }
+                    }
+                };
+
+                if scenery_packs != new_state.scenery_packs {
+                    let label = match action {
+                        ResolveAirportConflicts::DisableLosers { idents } => {
+                            let count = idents.len();
+                            LocalizedString::new(move || {
+                                fl!("airport-conflicts-resolved-operation", count = count)
+                            })
+                        }
+                        ResolveAirportConflicts::Prefer { ident, path } => {
+                            let (ident, path_debug) = (ident.clone(), format!("{:?}", path));
+                            LocalizedString::new(move || {
+                                fl!(
+                                    "airport-provider-preferred-operation",
+                                    ident = ident.clone(),
+                                    path = path_debug.clone()
+                                )
+                            })
+                        }
+                    };
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(label);
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::SelectSceneryPacks(action) => {
+                let scenery_packs = &new_state.scenery_packs;
+                let selection = Rc::make_mut(&mut new_state.selection);
+                match *action {
+                    SelectSceneryPacks::Only { index } => {
+                        selection.select_only(scenery_packs, index)
+                    }
+                    SelectSceneryPacks::Range { index } => {
+                        selection.select_range(scenery_packs, index)
+                    }
+                    SelectSceneryPacks::Toggle { index } => selection.toggle(scenery_packs, index),
+                    SelectSceneryPacks::All => selection.select_all(scenery_packs),
+                    SelectSceneryPacks::None => selection.clear(),
+                    SelectSceneryPacks::Invert => selection.invert(scenery_packs),
+                }
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::EnableSelected(action) => {
+                let changes: Vec<(usize, bool)> = new_state
+                    .selection
+                    .indices(&new_state.scenery_packs)
+                    .into_iter()
+                    .map(|index| (index, action.enabled))
+                    .collect();
+                let scenery_packs = if action.manage_libraries {
+                    new_state
+                        .dependencies
+                        .set_enabled_many(&new_state.scenery_packs, &changes)
+                } else {
+                    let mut scenery_packs = new_state.scenery_packs.clone();
+                    for (index, enabled) in &changes {
+                        scenery_packs[*index].enabled = *enabled;
+                    }
+                    scenery_packs
+                };
+
+                if scenery_packs != new_state.scenery_packs {
+                    let (count, enabled) = (changes.len(), action.enabled);
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(LocalizedString::new(move || {
+                        if enabled {
+                            fl!("scenery-packs-enabled-operation", count = count)
+                        } else {
+                            fl!("scenery-packs-disabled-operation", count = count)
+                        }
+                    }));
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::MoveSelected(action) => {
+                let selection = &new_state.selection;
+                let scenery_packs = match *action {
+                    MoveSelected::ToIndex(index) => {
+                        selection.move_to_index(&new_state.scenery_packs, index)
+                    }
+                    MoveSelected::ByOffset(offset) => {
+                        selection.move_by_offset(&new_state.scenery_packs, offset)
+                    }
+                };
+
+                if scenery_packs != new_state.scenery_packs {
+                    let count = selection.indices(&scenery_packs).len();
+                    new_state.scenery_packs = scenery_packs;
+                    new_state.push_scenery_packs_history(LocalizedString::new(move || {
+                        fl!("scenery-packs-moved-operation", count = count)
+                    }));
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::TagSelected(action) => {
+                let indices = new_state.selection.indices(&new_state.scenery_packs);
+                let mut tags = (*new_state.tags).clone();
+                let mut count = 0;
+                for index in &indices {
+                    let path = &new_state.scenery_packs[*index].path;
+                    let changed = if action.add {
+                        tags.add(path, &action.tag)
+                    } else {
+                        tags.remove(path, &action.tag)
+                    };
+                    if changed {
+                        count += 1;
+                    }
+                }
+
+                if count > 0 {
+                    new_state.tags = Rc::new(tags);
+                    let (tag, add) = (action.tag.clone(), action.add);
+                    new_state.push_scenery_packs_history(LocalizedString::new(move || {
+                        if add {
+                            fl!(
+                                "scenery-packs-tagged-operation",
+                                tag = tag.clone(),
+                                count = count
+                            )
+                        } else {
+                            fl!(
+                                "scenery-packs-untagged-operation",
+                                tag = tag.clone(),
+                                count = count
+                            )
+                        }
+                    }));
+                }
+
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+            ScenableAction::Preset(action) => {
+                match reduce_preset(&mut new_state, action) {
+                    // Only save the presets when the action succeeded
+                    // and changed them.
+                    Ok(()) if !Rc::ptr_eq(&prev_state.presets, &new_state.presets) => {
+                        if let Err(error) = new_state.presets.save() {
+                            tracing::error!("Error while saving presets: {}", error);
+                        }
+                    }
+                    Ok(()) => {}
+                    Err(error) => {
+                        tracing::error!("Error while performing preset action: {}", error);
+                    }
+                }
+                ReducerResult {
+                    state: Rc::new(new_state),
+                    events: vec![],
+                    effects: vec![],
+                }
+            }
+        }
+    }
+}
+
+/// Replace the scenery packs in `state`, recording the change in the
+/// history as specified by `action`.
+fn update_scenery_packs(state: &mut ScenableState, action: &UpdateSceneryPacks) {
+    state.scenery_packs = action.scenery_packs.clone();
+
+    if let ActionHistory::Some(label) = &action.history {
+        let history_item = ActionHistoryItem {
+            label: label.clone(),
+            item: state.scenery_packs_history_item(),
+        };
+
+        if action.reset_history {
+            state.scenery_packs_history.reset(history_item);
+        } else {
+            state.scenery_packs_history.push(history_item);
+        }
+    }
+}
+
+fn reduce_preset(state: &mut ScenableState, action: &PresetAction) -> eyre::Result<()> {
+    let current_scenery_packs = || state.scenery_packs.iter().cloned().collect();
+    match action {
+        PresetAction::Create { name } => {
+            let preset = Preset {
+                name: name.clone(),
+                scenery_packs: current_scenery_packs(),
+            };
+            Rc::make_mut(&mut state.presets).create(preset)
+        }
+        PresetAction::Apply { name } => {
+            let preset = state
+                .presets
+                .get(name)
+                .ok_or_else(|| eyre::eyre!("No preset named {:?} exists", name))?;
+            let scenery_packs = preset.apply(&state.scenery_packs);
+            if scenery_packs == state.scenery_packs {
+                return Ok(());
+            }
+            let preset_name = name.clone();
+            update_scenery_packs(
+                state,
+                &UpdateSceneryPacks {
+                    scenery_packs,
+                    history: ActionHistory::Some(LocalizedString::new(move || {
+                        fl!("preset-applied-operation", name = preset_name.clone())
+                    })),
+                    reset_history: false,
+                },
+            );
+            Ok(())
+        }
+        PresetAction::Update { name } => {
+            let scenery_packs = current_scenery_packs();
+            Rc::make_mut(&mut state.presets).update(name, scenery_packs)
+        }
+        PresetAction::Rename { name, new_name } => {
+            Rc::make_mut(&mut state.presets).rename(name, new_name.clone())
+        }
+        PresetAction::Delete { name } => Rc::make_mut(&mut state.presets).delete(name),
+        PresetAction::Duplicate { name, new_name } => {
+            Rc::make_mut(&mut state.presets).duplicate(name, new_name.clone())
+        }
+        PresetAction::AddSelected { name } => {
+            let selected: Vec<SceneryPack> = state
+                .selection
+                .indices(&state.scenery_packs)
+                .into_iter()
+                .map(|index| state.scenery_packs[index].clone())
+                .collect();
+            Rc::make_mut(&mut state.presets).add_scenery_packs(name, selected)
+        }
+    }
+}
+
+/// Create the history label for an [EnableSceneryPack] action, listing
+/// the libraries which were enabled or disabled along with the scenery
+/// pack at `index`.
+fn enable_scenery_pack_label(
+    prev_scenery_packs: &im_rc::Vector<SceneryPack>,
+    scenery_packs: &im_rc::Vector<SceneryPack>,
+    index: usize,
+) -> LocalizedString {
+    let mut enabled_libraries: Vec<String> = Vec::new();
+    let mut disabled_libraries: Vec<String> = Vec::new();
+    for (pack_index, (prev_pack, pack)) in prev_scenery_packs.iter().zip(scenery_packs).enumerate()
+    {
+        if pack_index == index || prev_pack.enabled == pack.enabled {
+            continue;
+        }
+        let path_debug = format!("{:?}", pack.path);
+        if pack.enabled {
+            enabled_libraries.push(path_debug);
+        } else {
+            disabled_libraries.push(path_debug);
+        }
+    }
+
+    let (path_debug, enabled) = match scenery_packs.get(index) {
+        Some(scenery_pack) => (format!("{:?}", scenery_pack.path), scenery_pack.enabled),
+        None => (String::new(), false),
+    };
+
+    LocalizedString::new(move || {
+        let mut label = if enabled {
+            fl!("scenery-pack-enabled-operation", path = path_debug.clone())
+        } else {
+            fl!("scenery-pack-disabled-operation", path = path_debug.clone())
+        };
+        if !enabled_libraries.is_empty() {
+            label.push_str(", ");
+            label.push_str(&fl!(
+                "libraries-enabled-operation",
+                paths = enabled_libraries.join(", ")
+            ));
+        }
+        if !disabled_libraries.is_empty() {
+            label.push_str(", ");
+            label.push_str(&fl!(
+                "libraries-disabled-operation",
+                paths = disabled_libraries.join(", ")
+            ));
+        }
+        label
+    })
+}
+
+/// Create the history label for an [EnableArea] action, with the
+/// number of scenery packs which were enabled and disabled.
+fn enable_area_label(
+    prev_scenery_packs: &im_rc::Vector<SceneryPack>,
+    scenery_packs: &im_rc::Vector<SceneryPack>,
+) -> LocalizedString {
+    let (mut enabled, mut disabled) = (0, 0);
+    for (prev_pack, pack) in prev_scenery_packs.iter().zip(scenery_packs) {
+        match (prev_pack.enabled, pack.enabled) {
+            (false, true) => enabled += 1,
+            (true, false) => disabled += 1,
+            _ => {}
         }
     }
+
+    LocalizedString::new(move || {
+        fl!(
+            "area-enabled-operation",
+            enabled = enabled,
+            disabled = disabled
+        )
+    })
 }
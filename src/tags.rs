@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use crate::settings::{read_config_file, write_config_file};
+
+/// Tags which the user has given to scenery packs, stored in
+/// `tags.ron` alongside the [Settings](crate::settings::Settings)
+/// file. Scenery packs are identified by
+/// [SceneryPack::path](crate::parsers::scenery_packs::SceneryPack::path).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Tags {
+    tags: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl Tags {
+    /// The tags of the specified scenery pack.
+    pub fn get(&self, path: &Path) -> impl Iterator<Item = &str> {
+        self.tags
+            .get(path)
+            .into_iter()
+            .flat_map(|tags| tags.iter().map(String::as_str))
+    }
+
+    /// Add `tag` to the scenery pack at `path`, returning whether it
+    /// did not already have it.
+    pub fn add(&mut self, path: &Path, tag: &str) -> bool {
+        self.tags
+            .entry(path.to_owned())
+            .or_default()
+            .insert(tag.to_string())
+    }
+
+    /// Remove `tag` from the scenery pack at `path`, returning whether
+    /// it had it.
+    pub fn remove(&mut self, path: &Path, tag: &str) -> bool {
+        let tags = match self.tags.get_mut(path) {
+            Some(tags) => tags,
+            None => return false,
+        };
+        let removed = tags.remove(tag);
+        if tags.is_empty() {
+            self.tags.remove(path);
+        }
+        removed
+    }
+
+    pub fn from_tags_file() -> eyre::Result<Self> {
+        Ok(read_config_file("tags.ron")?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        write_config_file("tags.ron", self)
+    }
+}
@@ -0,0 +1,154 @@
+use egui::{Color32, DragValue, Grid, TextEdit};
+
+use crate::{
+    fl,
+    geo::{GeoArea, LatLon},
+    state::{EnableArea, ScenableAction, ScenableStateRef},
+    view::View,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum AreaMode {
+    Circle,
+    BoundingBox,
+}
+
+/// A [View] for specifying a [GeoArea], and enabling only the scenery
+/// packs which cover it.
+pub struct AreaView {
+    state: ScenableStateRef,
+    mode: AreaMode,
+    /// Identifier of the airport at the centre of the circle.
+    airport: String,
+    centre: LatLon,
+    radius_nm: f64,
+    south: f64,
+    west: f64,
+    north: f64,
+    east: f64,
+}
+
+impl AreaView {
+    pub fn new(state: ScenableStateRef) -> Self {
+        Self {
+            state,
+            mode: AreaMode::Circle,
+            airport: String::new(),
+            centre: LatLon::default(),
+            radius_nm: 150.0,
+            south: 0.0,
+            west: 0.0,
+            north: 1.0,
+            east: 1.0,
+        }
+    }
+
+    fn area(&self) -> GeoArea {
+        match self.mode {
+            AreaMode::Circle => GeoArea::Circle {
+                centre: self.centre,
+                radius_nm: self.radius_nm,
+            },
+            AreaMode::BoundingBox => GeoArea::BoundingBox {
+                south: self.south,
+                west: self.west,
+                north: self.north,
+                east: self.east,
+            },
+        }
+    }
+}
+
+fn latitude(value: &mut f64) -> DragValue<'_> {
+    DragValue::new(value).speed(0.1).clamp_range(-90.0..=90.0)
+}
+
+fn longitude(value: &mut f64) -> DragValue<'_> {
+    DragValue::new(value).speed(0.1).clamp_range(-180.0..=180.0)
+}
+
+impl View for AreaView {
+    type Response = ();
+
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &epi::Frame) -> Self::Response {
+        let current_state = self.state.state();
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, AreaMode::Circle, fl!("area-circle-option"));
+            ui.radio_value(
+                &mut self.mode,
+                AreaMode::BoundingBox,
+                fl!("area-box-option"),
+            );
+        });
+
+        match self.mode {
+            AreaMode::Circle => {
+                Grid::new("area-circle-grid").show(ui, |ui| {
+                    ui.label(fl!("area-airport-label"));
+                    let response = ui.add(
+                        TextEdit::singleline(&mut self.airport).hint_text(fl!("area-airport-hint")),
+                    );
+                    ui.end_row();
+
+                    let ident = self.airport.trim();
+                    if !ident.is_empty() {
+                        match current_state.scenery_scan.airport_location(ident) {
+                            Some(location) if response.changed() => self.centre = location,
+                            Some(_) => {}
+                            None => {
+                                ui.label("");
+                                ui.colored_label(Color32::RED, fl!("area-airport-not-found"));
+                                ui.end_row();
+                            }
+                        }
+                    }
+
+                    ui.label(fl!("area-latitude-label"));
+                    ui.add(latitude(&mut self.centre.lat));
+                    ui.end_row();
+
+                    ui.label(fl!("area-longitude-label"));
+                    ui.add(longitude(&mut self.centre.lon));
+                    ui.end_row();
+
+                    ui.label(fl!("area-radius-label"));
+                    ui.add(
+                        DragValue::new(&mut self.radius_nm)
+                            .speed(1.0)
+                            .clamp_range(0.0..=10800.0),
+                    );
+                    ui.end_row();
+                });
+            }
+            AreaMode::BoundingBox => {
+                Grid::new("area-box-grid").show(ui, |ui| {
+                    ui.label(fl!("area-north-label"));
+                    ui.add(latitude(&mut self.north));
+                    ui.end_row();
+
+                    ui.label(fl!("area-south-label"));
+                    ui.add(latitude(&mut self.south));
+                    ui.end_row();
+
+                    ui.label(fl!("area-west-label"));
+                    ui.add(longitude(&mut self.west));
+                    ui.end_row();
+
+                    ui.label(fl!("area-east-label"));
+                    ui.add(longitude(&mut self.east));
+                    ui.end_row();
+                });
+            }
+        }
+
+        if ui
+            .button(fl!("area-enable-button"))
+            .on_hover_text(fl!("area-enable-hover-text"))
+            .clicked()
+        {
+            self.state
+                .dispatch(ScenableAction::EnableArea(EnableArea { area: self.area() }));
+        }
+    }
+}
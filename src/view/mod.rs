@@ -1,4 +1,8 @@
+pub mod area;
 pub mod page;
+pub mod presets;
+pub mod route;
+pub mod sort;
 
 pub trait View {
     type Response;
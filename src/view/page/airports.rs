@@ -0,0 +1,183 @@
+use std::rc::Rc;
+
+use egui::{ComboBox, Grid, RichText, ScrollArea, TextEdit};
+
+use crate::{
+    airports::AirportIndex,
+    fl,
+    state::{EnableAirport, ScenableAction, ScenableStateRef},
+};
+
+use super::{scenery_packs::scenery_pack_label, Page};
+
+/// The airports which matched a search, along with the index and
+/// filter they were found with, so that they are only searched for
+/// again when one of them changes.
+struct FilteredAirports {
+    airports: Rc<AirportIndex>,
+    search: String,
+    region: Option<String>,
+    /// The identifiers of the matching airports.
+    idents: Vec<String>,
+}
+
+/// A [Page] listing the airports provided by the scenery packs, which
+/// can be searched, and enabled by enabling the scenery packs which
+/// provide them.
+pub struct AirportsPage {
+    state: ScenableStateRef,
+    /// Only show airports whose identifier, ICAO code or name contains
+    /// this text.
+    search: String,
+    /// Only show airports in this region.
+    region: Option<String>,
+    /// The airports which match `search` and `region`.
+    filtered: Option<FilteredAirports>,
+}
+
+impl AirportsPage {
+    pub fn new(state: ScenableStateRef) -> Self {
+        Self {
+            state,
+            search: String::new(),
+            region: None,
+            filtered: None,
+        }
+    }
+
+    /// The identifiers of the airports in `airports` which match the
+    /// current search and region.
+    fn filter(&mut self, airports: &Rc<AirportIndex>) -> &[String] {
+        let up_to_date = matches!(
+            &self.filtered,
+            Some(filtered)
+                if Rc::ptr_eq(&filtered.airports, airports)
+                    && filtered.search == self.search
+                    && filtered.region == self.region
+        );
+        if !up_to_date {
+            let search = self.search.trim().to_lowercase();
+            let idents = airports
+                .iter()
+                .filter(|airport| match &self.region {
+                    Some(region) => airport.region.as_ref() == Some(region),
+                    None => true,
+                })
+                .filter(|airport| airport.matches(&search))
+                .map(|airport| airport.ident.clone())
+                .collect();
+            self.filtered = Some(FilteredAirports {
+                airports: airports.clone(),
+                search: self.search.clone(),
+                region: self.region.clone(),
+                idents,
+            });
+        }
+        match &self.filtered {
+            Some(filtered) => &filtered.idents,
+            None => &[],
+        }
+    }
+
+    fn filter_ui(&mut self, ui: &mut egui::Ui, airports: &AirportIndex) {
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.search).hint_text(fl!("airport-search-hint")));
+
+            let region_text = match &self.region {
+                Some(region) => region.clone(),
+                None => fl!("airport-region-all"),
+            };
+            ComboBox::from_id_source("airport-region-filter")
+                .selected_text(region_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.region, None, fl!("airport-region-all"));
+                    for region in airports.regions() {
+                        ui.selectable_value(&mut self.region, Some(region.to_string()), region);
+                    }
+                })
+                .response
+                .on_hover_text(fl!("airport-region-hover-text"));
+        });
+    }
+}
+
+impl Page for AirportsPage {
+    type Response = ();
+
+    fn show(&mut self, ctx: &egui::CtxRef, _frame: &epi::Frame) -> Self::Response {
+        let current_state = self.state.state();
+        let airports = current_state.airports.clone();
+        let scenery_packs = &current_state.scenery_packs;
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(fl!("airports-heading"));
+            if airports.iter().next().is_none() {
+                ui.label(fl!("no-airports-label"));
+                return;
+            }
+            self.filter_ui(ui, &airports);
+
+            let state = self.state.clone();
+            let idents = self.filter(&airports);
+            let count = idents.len();
+            ui.label(fl!("airport-count-label", count = count));
+
+            let row_height = ui.spacing().interact_size.y;
+            ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show_rows(ui, row_height, idents.len(), |ui, rows| {
+                    Grid::new("airports-grid").striped(true).show(ui, |ui| {
+                        ui.strong(fl!("airport-enabled-column"));
+                        ui.strong(fl!("airport-icao-column"));
+                        ui.strong(fl!("airport-name-column"));
+                        ui.strong(fl!("airport-region-column"));
+                        ui.strong(fl!("airport-providers-column"));
+                        ui.end_row();
+
+                        for ident in &idents[rows] {
+                            let airport = match airports.get(ident) {
+                                Some(airport) => airport,
+                                None => continue,
+                            };
+
+                            let listed = airport.is_listed(scenery_packs);
+                            let was_enabled = airport.is_enabled(scenery_packs);
+                            let mut enabled = was_enabled;
+                            let response =
+                                ui.add_enabled(listed, egui::Checkbox::new(&mut enabled, ""));
+                            let response = if listed {
+                                response.on_hover_text(fl!("airport-enable-hover-text"))
+                            } else {
+                                response
+                                    .on_disabled_hover_text(fl!("airport-not-listed-hover-text"))
+                            };
+                            if response.changed() && enabled != was_enabled {
+                                state.dispatch(ScenableAction::EnableAirport(EnableAirport {
+                                    ident: airport.ident.clone(),
+                                    enabled,
+                                }));
+                            }
+
+                            ui.label(&airport.icao);
+                            ui.label(&airport.name);
+                            ui.label(airport.region.as_deref().unwrap_or_default());
+                            ui.horizontal(|ui| {
+                                for path in &airport.providers {
+                                    let pack_enabled = scenery_packs.iter().any(|scenery_pack| {
+                                        scenery_pack.enabled && &scenery_pack.path == path
+                                    });
+                                    let text = RichText::new(scenery_pack_label(path)).small();
+                                    if pack_enabled {
+                                        ui.label(text);
+                                    } else {
+                                        ui.label(text.weak());
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+        });
+    }
+}
@@ -0,0 +1,223 @@
+use egui::{DragValue, Grid, RichText, ScrollArea};
+
+use crate::{
+    backup::{Backup, Backups},
+    diff::SceneryPacksDiff,
+    fl,
+    i18n::LocalizedString,
+    parsers::scenery_packs::{read_scenery_packs_ini, scenery_packs_ini_path, SceneryPack},
+    state::{ActionHistory, ScenableAction, ScenableStateRef, UpdateSceneryPacks},
+};
+
+use super::{
+    scenery_packs::{change_badges_ui, scenery_pack_label},
+    Page,
+};
+
+/// A backup of `scenery_packs.ini` along with its scenery packs, or
+/// the error encountered while reading them.
+struct BackupEntry {
+    backup: Backup,
+    scenery_packs: Result<im_rc::Vector<SceneryPack>, String>,
+}
+
+/// A [Page] listing the backups of `scenery_packs.ini`, showing how
+/// each differs from the current scenery packs, with buttons to
+/// restore them.
+pub struct BackupsPage {
+    state: ScenableStateRef,
+    entries: Vec<BackupEntry>,
+    /// The index in `entries` of the backup whose differences are
+    /// shown in detail.
+    expanded: Option<usize>,
+}
+
+impl BackupsPage {
+    pub fn new(state: ScenableStateRef) -> Self {
+        let mut new_self = Self {
+            state,
+            entries: Vec::new(),
+            expanded: None,
+        };
+        new_self.refresh();
+        new_self
+    }
+
+    /// Re-read the list of backups of the current X-Plane
+    /// installation's `scenery_packs.ini` from disk.
+    pub fn refresh(&mut self) {
+        let xplane_dir = self.state.state().settings.xplane_dir.clone();
+        let backups = match xplane_dir {
+            Some(xplane_dir) => {
+                let ini_path = scenery_packs_ini_path(&xplane_dir);
+                Backups::from_data_dir(&ini_path).and_then(|backups| backups.list())
+            }
+            None => Ok(Vec::new()),
+        };
+        let backups = match backups {
+            Ok(backups) => backups,
+            Err(error) => {
+                tracing::error!("Error while listing backups: {}", error);
+                Vec::new()
+            }
+        };
+        self.entries = backups
+            .into_iter()
+            .map(|backup| {
+                let scenery_packs = read_scenery_packs_ini(&backup.path)
+                    .map(|ini| ini.scenery_packs.into())
+                    .map_err(|error| error.to_string());
+                BackupEntry {
+                    backup,
+                    scenery_packs,
+                }
+            })
+            .collect();
+        self.expanded = None;
+    }
+
+    /// Replace the current scenery packs with those in a backup. This
+    /// is recorded in the history, and is not written to disk until the
+    /// scenery packs are saved.
+    fn restore(&self, backup: &Backup, scenery_packs: &im_rc::Vector<SceneryPack>) {
+        let time = format_time(backup);
+        self.state
+            .dispatch(ScenableAction::UpdateSceneryPacks(UpdateSceneryPacks {
+                scenery_packs: scenery_packs.clone(),
+                history: ActionHistory::Some(LocalizedString::new(move || {
+                    fl!("backup-restored-operation", time = time.clone())
+                })),
+                reset_history: false,
+            }));
+    }
+
+    fn backup_count_ui(&mut self, ui: &mut egui::Ui) {
+        let settings = self.state.state().settings.clone();
+        ui.horizontal(|ui| {
+            ui.label(fl!("backup-count-label"));
+            let mut backup_count = settings.backup_count;
+            if ui
+                .add(DragValue::new(&mut backup_count).clamp_range(0..=1000))
+                .changed()
+            {
+                let mut settings = (*settings).clone();
+                settings.backup_count = backup_count;
+                if let Err(error) = settings.save() {
+                    tracing::error!("Error while saving settings: {}", error);
+                }
+                self.state
+                    .dispatch(ScenableAction::UpdateSettings(settings));
+            }
+            if ui
+                .button("🔄")
+                .on_hover_text(fl!("refresh-backups-hover-text"))
+                .clicked()
+            {
+                self.refresh();
+            }
+        });
+    }
+}
+
+impl Page for BackupsPage {
+    type Response = ();
+
+    fn show(&mut self, ctx: &egui::CtxRef, _frame: &epi::Frame) -> Self::Response {
+        let current_state = self.state.state();
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(fl!("backups-heading"));
+            self.backup_count_ui(ui);
+
+            if self.entries.is_empty() {
+                ui.label(fl!("no-backups-label"));
+                return;
+            }
+
+            ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    let mut expanded = self.expanded;
+                    Grid::new("backups-grid").striped(true).show(ui, |ui| {
+                        ui.strong(fl!("backup-time-column"));
+                        ui.strong(fl!("backup-enabled-column"));
+                        ui.strong(fl!("backup-disabled-column"));
+                        ui.strong(fl!("backup-changes-column"));
+                        ui.end_row();
+
+                        for (index, entry) in self.entries.iter().enumerate() {
+                            ui.label(format_time(&entry.backup));
+                            let scenery_packs = match &entry.scenery_packs {
+                                Ok(scenery_packs) => scenery_packs,
+                                Err(error) => {
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.colored_label(egui::Color32::RED, error);
+                                    ui.end_row();
+                                    continue;
+                                }
+                            };
+
+                            let enabled = scenery_packs
+                                .iter()
+                                .filter(|scenery_pack| scenery_pack.enabled)
+                                .count();
+                            ui.label(enabled.to_string());
+                            ui.label((scenery_packs.len() - enabled).to_string());
+
+                            // The changes which restoring the backup
+                            // would make to the current scenery packs.
+                            let diff =
+                                SceneryPacksDiff::new(&current_state.scenery_packs, scenery_packs);
+                            ui.horizontal(|ui| {
+                                let is_expanded = expanded == Some(index);
+                                let text = if diff.is_empty() {
+                                    fl!("backup-unchanged-label")
+                                } else {
+                                    fl!("backup-changes-label", count = diff.changes.len())
+                                };
+                                if ui
+                                    .add_enabled(
+                                        !diff.is_empty(),
+                                        egui::SelectableLabel::new(is_expanded, text),
+                                    )
+                                    .on_hover_text(fl!("backup-changes-hover-text"))
+                                    .clicked()
+                                {
+                                    expanded = if is_expanded { None } else { Some(index) };
+                                }
+                                if ui
+                                    .small_button(fl!("restore-backup-button"))
+                                    .on_hover_text(fl!("restore-backup-hover-text"))
+                                    .clicked()
+                                {
+                                    self.restore(&entry.backup, scenery_packs);
+                                }
+                            });
+                            ui.end_row();
+
+                            if expanded == Some(index) {
+                                for change in &diff.changes {
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new(scenery_pack_label(&change.path)).small(),
+                                        );
+                                        change_badges_ui(ui, change);
+                                    });
+                                    ui.end_row();
+                                }
+                            }
+                        }
+                    });
+                    self.expanded = expanded;
+                });
+        });
+    }
+}
+
+fn format_time(backup: &Backup) -> String {
+    format!("{} UTC", backup.time.format("%Y-%m-%d %H:%M:%S"))
+}
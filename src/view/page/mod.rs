@@ -1,3 +1,5 @@
+pub mod airports;
+pub mod backups;
 pub mod scenery_packs;
 pub mod setup;
 
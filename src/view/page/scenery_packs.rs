@@ -1,27 +1,140 @@
 use super::Page;
 use crate::{
+    backup::{write_atomic, Backups},
+    classify::SceneryCategory,
+    dependencies::{DependencyGraph, PathsByPack},
+    diff::{SceneryPackChange, SceneryPacksDiff},
     fl,
     i18n::LocalizedString,
+    merge::merge,
     parsers::{
         inifile::ToIniFile,
-        scenery_packs::{scenery_packs_ini, SceneryPacksIni},
+        scenery_packs::{
+            read_scenery_packs_ini, scenery_packs_ini_path, SceneryPack, SceneryPackPath,
+            SceneryPacksIni, DEFAULT_VERSION, GLOBAL_AIRPORTS_PATH,
+        },
     },
+    scan::SceneryScanner,
     state::{
-        ActionHistory, ScenableAction, ScenableStateRef, UpdateSceneryPack, UpdateSceneryPacks,
+        ActionHistory, AddSceneryPacks, EnableSceneryPack, EnableSelected, MergeSceneryPacks,
+        MoveSelected, PresetAction, RemoveSceneryPacks, ResolveAirportConflicts, ScenableAction,
+        ScenableState, ScenableStateRef, SelectSceneryPacks, TagSelected, UpdateSceneryPack,
+        UpdateSceneryPacks,
     },
+    view::{area::AreaView, presets::PresetsView, route::RouteView, sort::SortView, View},
 };
 
-use egui::{Button, ScrollArea};
+use egui::{
+    Button, Color32, ComboBox, CursorIcon, DragValue, Label, RichText, ScrollArea, Sense, TextEdit,
+};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
 
-use std::path::{Path, PathBuf};
+/// How often to check whether `scenery_packs.ini` has been modified by
+/// another program, in seconds.
+const INI_CHECK_INTERVAL: f64 = 1.0;
+
+/// Identifies the contents of `scenery_packs.ini` at the time that it
+/// was last read or written, so that modifications made by other
+/// programs can be detected.
+#[derive(Clone, Copy, PartialEq)]
+struct IniFileVersion {
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+impl IniFileVersion {
+    fn read(ini_path: &Path) -> eyre::Result<Self> {
+        let modified = std::fs::metadata(ini_path)?.modified().ok();
+        let mut hasher = DefaultHasher::new();
+        std::fs::read(ini_path)?.hash(&mut hasher);
+        Ok(Self {
+            modified,
+            hash: hasher.finish(),
+        })
+    }
+}
 
 pub struct SceneryPacksPage {
     state: ScenableStateRef,
+    scanner: SceneryScanner,
+    /// Whether the scenery packs need to be scanned, because they have
+    /// not yet been scanned or have been re-read from disk.
+    scan_required: bool,
+    /// Whether to automatically enable and disable libraries when a
+    /// scenery pack is enabled or disabled.
+    manage_libraries: bool,
+    /// Only show scenery packs in this category.
+    category_filter: Option<SceneryCategory>,
+    presets_view: PresetsView,
+    area_view: AreaView,
+    route_view: RouteView,
+    sort_view: SortView,
+    /// Whether the window for sorting the scenery packs is open.
+    sort_open: bool,
+    /// The contents of `scenery_packs.ini` when it was last read or
+    /// written, whose layout is kept when it is saved.
+    ini: SceneryPacksIni,
+    /// Whether the user has chosen what to do with the
+    /// [invalid lines](SceneryPacksIni::invalid_lines) in `ini`, which
+    /// is required before it can be saved.
+    invalid_lines_acknowledged: bool,
+    /// The version of `scenery_packs.ini` which was last read or
+    /// written.
+    ini_version: Option<IniFileVersion>,
+    /// The time (see [egui::InputState::time]) that `scenery_packs.ini`
+    /// was last checked for modifications.
+    ini_checked_time: f64,
+    /// The contents of `scenery_packs.ini`, if it has been modified by
+    /// another program and the user has not yet chosen what to do about
+    /// it. `ini` is only replaced with it when the user chooses to
+    /// reload or merge.
+    external_ini: Option<SceneryPacksIni>,
+    /// Messages about conflicts in the most recent merge.
+    merge_messages: Vec<String>,
+    /// The tag to add to or remove from the selected scenery packs.
+    tag: String,
+    /// The position to move the selected scenery packs to, starting
+    /// from 1 as displayed.
+    move_position: usize,
+    /// Whether the selected scenery packs are being dragged to a new
+    /// position in the list.
+    dragging: bool,
+    /// The index which the dragged scenery packs will be moved to (see
+    /// [MoveSelected::ToIndex]) when they are dropped.
+    drop_index: Option<usize>,
 }
 
 impl SceneryPacksPage {
     pub fn new(state: ScenableStateRef) -> Self {
-        let mut new_self = Self { state };
+        let mut new_self = Self {
+            presets_view: PresetsView::new(state.clone()),
+            area_view: AreaView::new(state.clone()),
+            route_view: RouteView::new(state.clone()),
+            sort_view: SortView::new(state.clone()),
+            sort_open: false,
+            ini: SceneryPacksIni::new(DEFAULT_VERSION, Vec::new()),
+            invalid_lines_acknowledged: true,
+            ini_version: None,
+            ini_checked_time: 0.0,
+            external_ini: None,
+            merge_messages: Vec::new(),
+            state,
+            scanner: SceneryScanner::default(),
+            scan_required: false,
+            manage_libraries: true,
+            category_filter: None,
+            tag: String::new(),
+            move_position: 1,
+            dragging: false,
+            drop_index: None,
+        };
 
         if let Err(error) = new_self.read_scenery_packs(true) {
             tracing::error!("Error while reading scenery packs: {}", error);
@@ -36,22 +149,27 @@ impl SceneryPacksPage {
             eyre::eyre!("xplane_dir needs to be set before reading scenery packs")
         })?;
 
-        Ok(xplane_dir.join("Custom Scenery").join("scenery_packs.ini"))
+        Ok(scenery_packs_ini_path(xplane_dir))
     }
 
     fn save_scenery_packs(&mut self) -> eyre::Result<()> {
+        if !self.invalid_lines_acknowledged {
+            return Err(eyre::eyre!(
+                "The invalid lines in scenery_packs.ini need to be kept or removed before saving"
+            ));
+        }
         let ini_path = self.scenery_packs_ini_path()?;
-        let ini = SceneryPacksIni {
-            version: 1000,
-            scenery_packs: self
-                .state
-                .state()
-                .scenery_packs
-                .clone()
-                .into_iter()
-                .collect(),
-        };
-        write_scenery_packs_ini(&ini, ini_path)?;
+
+        let state = self.state.state();
+        let ini = self
+            .ini
+            .with_scenery_packs(state.scenery_packs.iter().cloned().collect());
+        let backup_count = state.settings.backup_count;
+        write_scenery_packs_ini(&ini, &ini_path, backup_count)?;
+        self.ini = ini;
+        self.ini_version = Some(IniFileVersion::read(&ini_path)?);
+        self.external_ini = None;
+        self.state.state().tags.save()?;
 
         self.state
             .dispatch(ScenableAction::UpdateSceneryPacksSyncStatus);
@@ -59,14 +177,38 @@ impl SceneryPacksPage {
         Ok(())
     }
 
+    /// Show a banner when the scenery packs are missing the
+    /// `*GLOBAL_AIRPORTS*` entry which the installed version of X-Plane
+    /// requires (see [ScenableState::missing_global_airports()]),
+    /// with a button to add it.
+    fn missing_entries_ui(&self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::YELLOW, fl!("global-airports-missing-label"));
+            if ui
+                .button(fl!("add-global-airports-button"))
+                .on_hover_text(fl!("add-global-airports-hover-text"))
+                .clicked()
+            {
+                self.state
+                    .dispatch(ScenableAction::AddSceneryPacks(AddSceneryPacks {
+                        paths: vec![PathBuf::from(GLOBAL_AIRPORTS_PATH)],
+                    }));
+            }
+        });
+    }
+
     /// Read scenery packs ini file, and replace what is the current
     /// state of scenery packs.
     fn read_scenery_packs(&mut self, reset_history: bool) -> eyre::Result<()> {
         let ini_path = self.scenery_packs_ini_path()?;
-        let ini = read_scenery_packs_ini(ini_path)?;
+        let ini = read_scenery_packs_ini(&ini_path)?;
+        self.ini_version = Some(IniFileVersion::read(&ini_path)?);
+        self.external_ini = None;
+        self.invalid_lines_acknowledged = ini.invalid_lines.is_empty();
+        self.ini = ini;
         self.state
             .dispatch(ScenableAction::UpdateSceneryPacks(UpdateSceneryPacks {
-                scenery_packs: im_rc::Vector::from(ini.scenery_packs),
+                scenery_packs: self.ini.scenery_packs.iter().cloned().collect(),
                 history: ActionHistory::Some(LocalizedString::from("Read scenery_packs.ini")),
                 reset_history,
             }));
@@ -74,13 +216,574 @@ impl SceneryPacksPage {
         self.state
             .dispatch(ScenableAction::UpdateSceneryPacksSyncStatus);
 
+        self.scan_required = true;
+
+        Ok(())
+    }
+
+    /// Check whether `scenery_packs.ini` has been modified by another
+    /// program since it was last read or written, and if so, read the
+    /// new contents into `external_ini`.
+    fn check_ini_modified(&mut self) -> eyre::Result<()> {
+        let ini_path = self.scenery_packs_ini_path()?;
+        let ini_version = match &mut self.ini_version {
+            Some(ini_version) => ini_version,
+            None => return Ok(()),
+        };
+        let modified = std::fs::metadata(&ini_path)?.modified().ok();
+        if modified == ini_version.modified {
+            return Ok(());
+        }
+
+        let new_version = IniFileVersion::read(&ini_path)?;
+        let changed = new_version.hash != ini_version.hash;
+        *ini_version = new_version;
+        if changed {
+            tracing::info!("{:?} was modified by another program", ini_path);
+            self.external_ini = Some(read_scenery_packs_ini(&ini_path)?);
+        }
         Ok(())
     }
+
+    /// Show a banner when `scenery_packs.ini` has been modified by
+    /// another program, offering to reload it, merge it with the
+    /// current scenery packs, or overwrite it.
+    fn external_changes_ui(&mut self, ui: &mut egui::Ui) {
+        if !self.merge_messages.is_empty() {
+            let mut dismiss = false;
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    for message in &self.merge_messages {
+                        ui.colored_label(Color32::YELLOW, message);
+                    }
+                });
+                dismiss = ui.small_button("✖").clicked();
+            });
+            if dismiss {
+                self.merge_messages.clear();
+            }
+        }
+
+        let on_disk: im_rc::Vector<SceneryPack> = match &self.external_ini {
+            Some(external_ini) => external_ini.scenery_packs.iter().cloned().collect(),
+            None => return,
+        };
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::YELLOW, fl!("ini-modified-label"));
+            if ui
+                .button(fl!("ini-reload-button"))
+                .on_hover_text(fl!("ini-reload-hover-text"))
+                .clicked()
+            {
+                if let Err(error) = self.read_scenery_packs(false) {
+                    tracing::error!("Error while reading scenery packs: {}", error);
+                }
+            }
+            if ui
+                .button(fl!("ini-merge-button"))
+                .on_hover_text(fl!("ini-merge-hover-text"))
+                .clicked()
+            {
+                let current_state = self.state.state();
+                let result = merge(
+                    &current_state.saved_scenery_packs,
+                    &current_state.scenery_packs,
+                    &on_disk,
+                );
+                self.merge_messages.clear();
+                if !result.conflicts.is_empty() {
+                    let count = result.conflicts.len();
+                    self.merge_messages
+                        .push(fl!("ini-merge-removal-conflicts-label", count = count));
+                }
+                if result.order_conflict {
+                    self.merge_messages
+                        .push(fl!("ini-merge-order-conflict-label"));
+                }
+                self.state
+                    .dispatch(ScenableAction::MergeSceneryPacks(MergeSceneryPacks {
+                        on_disk,
+                        merged: result.scenery_packs,
+                    }));
+                if let Some(external_ini) = self.external_ini.take() {
+                    self.invalid_lines_acknowledged = external_ini.invalid_lines.is_empty();
+                    self.ini = external_ini;
+                }
+                self.scan_required = true;
+            }
+            if ui
+                .button(fl!("ini-overwrite-button"))
+                .on_hover_text(fl!("ini-overwrite-hover-text"))
+                .clicked()
+            {
+                if let Err(error) = self.save_scenery_packs() {
+                    tracing::error!("Error saving scenery_packs.ini: {}", error);
+                }
+            }
+        });
+    }
+
+    /// Start scanning the current scenery packs in the background.
+    fn start_scan(&mut self, frame: &epi::Frame) -> eyre::Result<()> {
+        let state = self.state.state();
+        let xplane_dir: PathBuf = state.settings.xplane_dir.clone().ok_or_else(|| {
+            eyre::eyre!("xplane_dir needs to be set before scanning scenery packs")
+        })?;
+        let pack_paths = state
+            .scenery_packs
+            .iter()
+            .map(|scenery_pack| scenery_pack.path.clone())
+            .collect();
+
+        let scan_frame = frame.clone();
+        self.scanner
+            .start(xplane_dir, pack_paths, move || scan_frame.request_repaint());
+
+        Ok(())
+    }
+
+    /// Show the buttons for changing the selection, and for performing
+    /// bulk actions on the selected scenery packs.
+    fn selection_ui(&mut self, ui: &mut egui::Ui, current_state: &ScenableState) {
+        let selected_count = current_state
+            .selection
+            .indices(&current_state.scenery_packs)
+            .len();
+        let has_selection = selected_count > 0;
+
+        ui.horizontal(|ui| {
+            ui.label(fl!("selected-scenery-packs-label", count = selected_count));
+            let select_actions = [
+                (SelectSceneryPacks::All, fl!("select-all-button")),
+                (SelectSceneryPacks::None, fl!("select-none-button")),
+                (SelectSceneryPacks::Invert, fl!("select-invert-button")),
+            ];
+            for (action, text) in select_actions {
+                if ui.small_button(text).clicked() {
+                    self.state
+                        .dispatch(ScenableAction::SelectSceneryPacks(action));
+                }
+            }
+
+            ui.separator();
+
+            for (enabled, text, hover_text) in [
+                (
+                    true,
+                    fl!("enable-selected-button"),
+                    fl!("enable-selected-hover-text"),
+                ),
+                (
+                    false,
+                    fl!("disable-selected-button"),
+                    fl!("disable-selected-hover-text"),
+                ),
+            ] {
+                if ui
+                    .add_enabled(has_selection, Button::new(text))
+                    .on_hover_text(hover_text)
+                    .clicked()
+                {
+                    self.state
+                        .dispatch(ScenableAction::EnableSelected(EnableSelected {
+                            enabled,
+                            manage_libraries: self.manage_libraries,
+                        }));
+                }
+            }
+
+            ui.separator();
+
+            let pack_count = current_state.scenery_packs.len();
+            for (action, text, hover_text) in [
+                (
+                    MoveSelected::ToIndex(0),
+                    "⏫",
+                    fl!("move-selected-top-hover-text"),
+                ),
+                (
+                    MoveSelected::ByOffset(-1),
+                    "⬆",
+                    fl!("move-selected-up-hover-text"),
+                ),
+                (
+                    MoveSelected::ByOffset(1),
+                    "⬇",
+                    fl!("move-selected-down-hover-text"),
+                ),
+                (
+                    MoveSelected::ToIndex(pack_count),
+                    "⏬",
+                    fl!("move-selected-bottom-hover-text"),
+                ),
+            ] {
+                if ui
+                    .add_enabled(has_selection, Button::new(text))
+                    .on_hover_text(hover_text)
+                    .clicked()
+                {
+                    self.state.dispatch(ScenableAction::MoveSelected(action));
+                }
+            }
+            if ui
+                .add_enabled(has_selection, Button::new(fl!("move-selected-button")))
+                .on_hover_text(fl!("move-selected-hover-text"))
+                .clicked()
+            {
+                self.state
+                    .dispatch(ScenableAction::MoveSelected(MoveSelected::ToIndex(
+                        self.move_position.saturating_sub(1).min(pack_count),
+                    )));
+            }
+            ui.add(DragValue::new(&mut self.move_position).clamp_range(1..=pack_count + 1));
+
+            ui.separator();
+
+            ui.add(
+                TextEdit::singleline(&mut self.tag)
+                    .desired_width(100.0)
+                    .hint_text(fl!("tag-hint")),
+            );
+            let tag = self.tag.trim().to_string();
+            let can_tag = has_selection && !tag.is_empty();
+            for (add, text, hover_text) in [
+                (true, "🏷", fl!("tag-selected-hover-text")),
+                (false, "✖", fl!("untag-selected-hover-text")),
+            ] {
+                if ui
+                    .add_enabled(can_tag, Button::new(text))
+                    .on_hover_text(hover_text)
+                    .clicked()
+                {
+                    self.state
+                        .dispatch(ScenableAction::TagSelected(TagSelected {
+                            tag: tag.clone(),
+                            add,
+                        }));
+                }
+            }
+
+            ui.separator();
+
+            ui.add_enabled_ui(
+                has_selection && !current_state.presets.presets.is_empty(),
+                |ui| {
+                    ui.menu_button(fl!("add-selected-to-preset-button"), |ui| {
+                        for preset in &current_state.presets.presets {
+                            if ui.button(&preset.name).clicked() {
+                                self.state.dispatch(ScenableAction::Preset(
+                                    PresetAction::AddSelected {
+                                        name: preset.name.clone(),
+                                    },
+                                ));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                },
+            );
+        });
+    }
+
+    /// While dragging scenery packs, update the drop position if the
+    /// pointer is over the row for the scenery pack at `index`, and
+    /// draw a line where the dragged scenery packs will be placed.
+    fn drag_target_ui(&mut self, ui: &mut egui::Ui, row_rect: egui::Rect, index: usize) {
+        let pointer = match ui.input().pointer.hover_pos() {
+            Some(pointer) => pointer,
+            None => return,
+        };
+        if pointer.y < row_rect.top() || pointer.y >= row_rect.bottom() {
+            return;
+        }
+
+        let (drop_index, y) = if pointer.y < row_rect.center().y {
+            (index, row_rect.top())
+        } else {
+            (index + 1, row_rect.bottom())
+        };
+        self.drop_index = Some(drop_index);
+        ui.painter().line_segment(
+            [
+                egui::pos2(row_rect.left(), y),
+                egui::pos2(row_rect.right(), y),
+            ],
+            ui.visuals().selection.stroke,
+        );
+    }
+
+    /// Show the lines of `scenery_packs.ini` which could not be read,
+    /// with buttons to choose whether to keep or remove them when it is
+    /// saved.
+    fn invalid_lines_ui(&mut self, ui: &mut egui::Ui) {
+        ui.colored_label(
+            Color32::YELLOW,
+            fl!("invalid-lines-label", count = self.ini.invalid_lines.len()),
+        );
+        for error in &self.ini.invalid_lines {
+            let line = error.line;
+            let column = error.column;
+            let expected = error.expected;
+            ui.horizontal(|ui| {
+                ui.label(fl!(
+                    "invalid-line-label",
+                    line = line,
+                    column = column,
+                    expected = expected
+                ));
+                ui.monospace(String::from_utf8_lossy(&error.text).as_ref());
+            });
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .button(fl!("keep-invalid-lines-button"))
+                .on_hover_text(fl!("keep-invalid-lines-hover-text"))
+                .clicked()
+            {
+                self.ini.keep_invalid_lines = true;
+                self.invalid_lines_acknowledged = true;
+            }
+            if ui
+                .button(fl!("remove-invalid-lines-button"))
+                .on_hover_text(fl!("remove-invalid-lines-hover-text"))
+                .clicked()
+            {
+                self.ini.keep_invalid_lines = false;
+                self.invalid_lines_acknowledged = true;
+            }
+        });
+    }
+
+    /// Show the airports which are provided by more than one enabled
+    /// scenery pack, which of them X-Plane uses, and buttons to prefer
+    /// another scenery pack or disable the ignored ones.
+    fn airport_conflicts_ui(&self, ui: &mut egui::Ui, current_state: &ScenableState) {
+        let conflicts = current_state
+            .airports
+            .conflicts(&current_state.scenery_packs);
+        if conflicts.is_empty() {
+            return;
+        }
+
+        let heading = fl!("airport-conflicts-heading", count = conflicts.len());
+        ui.collapsing(heading, |ui| {
+            if ui
+                .button(fl!("disable-all-airport-losers-button"))
+                .on_hover_text(fl!("disable-all-airport-losers-hover-text"))
+                .clicked()
+            {
+                self.state.dispatch(ScenableAction::ResolveAirportConflicts(
+                    ResolveAirportConflicts::DisableLosers {
+                        idents: conflicts
+                            .iter()
+                            .map(|conflict| conflict.ident.clone())
+                            .collect(),
+                    },
+                ));
+            }
+            for conflict in &conflicts {
+                let ident = conflict.ident.clone();
+                ui.horizontal_wrapped(|ui| {
+                    ui.strong(fl!("airport-conflict-label", ident = ident));
+                    ui.label(
+                        RichText::new(scenery_pack_label(conflict.winner())).color(Color32::GREEN),
+                    )
+                    .on_hover_text(fl!("airport-conflict-winner-hover-text"));
+                    for path in conflict.losers() {
+                        ui.label(RichText::new(scenery_pack_label(path)).strikethrough())
+                            .on_hover_text(fl!("airport-conflict-loser-hover-text"));
+                        if ui
+                            .small_button("⬆")
+                            .on_hover_text(fl!("prefer-airport-provider-hover-text"))
+                            .clicked()
+                        {
+                            self.state.dispatch(ScenableAction::ResolveAirportConflicts(
+                                ResolveAirportConflicts::Prefer {
+                                    ident: conflict.ident.clone(),
+                                    path: path.clone(),
+                                },
+                            ));
+                        }
+                    }
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text(fl!("disable-airport-losers-hover-text"))
+                        .clicked()
+                    {
+                        self.state.dispatch(ScenableAction::ResolveAirportConflicts(
+                            ResolveAirportConflicts::DisableLosers {
+                                idents: vec![conflict.ident.clone()],
+                            },
+                        ));
+                    }
+                });
+            }
+        });
+    }
+
+    /// Show the changes which have been made since `scenery_packs.ini`
+    /// was last read or saved.
+    fn pending_changes_ui(&self, ui: &mut egui::Ui, diff: &SceneryPacksDiff) {
+        if diff.is_empty() {
+            return;
+        }
+        let heading = fl!("pending-changes-heading", count = diff.changes.len());
+        ui.collapsing(heading, |ui| {
+            for change in &diff.changes {
+                ui.horizontal(|ui| {
+                    ui.label(scenery_pack_label(&change.path));
+                    change_badges_ui(ui, change);
+                });
+            }
+        });
+    }
+
+    /// Show the scenery packs which have been installed or removed
+    /// since `scenery_packs.ini` was written, with buttons to add or
+    /// remove them.
+    fn pack_changes_ui(
+        &self,
+        ui: &mut egui::Ui,
+        current_state: &ScenableState,
+        removed_scenery_packs: &[PathBuf],
+    ) {
+        let new_scenery_packs = current_state.new_scenery_packs();
+
+        if !new_scenery_packs.is_empty() {
+            let heading = fl!("new-scenery-packs-heading", count = new_scenery_packs.len());
+            ui.collapsing(heading, |ui| {
+                if ui
+                    .button(fl!("add-all-scenery-packs-button"))
+                    .on_hover_text(fl!("add-all-scenery-packs-hover-text"))
+                    .clicked()
+                {
+                    self.state
+                        .dispatch(ScenableAction::AddSceneryPacks(AddSceneryPacks {
+                            paths: new_scenery_packs.clone(),
+                        }));
+                }
+                for path in &new_scenery_packs {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .small_button("➕")
+                            .on_hover_text(fl!("add-scenery-pack-hover-text"))
+                            .clicked()
+                        {
+                            self.state
+                                .dispatch(ScenableAction::AddSceneryPacks(AddSceneryPacks {
+                                    paths: vec![path.clone()],
+                                }));
+                        }
+                        ui.label(scenery_pack_label(path));
+                        let categories = current_state
+                            .scenery_scan
+                            .packs
+                            .get(path)
+                            .map(|pack_scan| &pack_scan.categories);
+                        for category in categories.into_iter().flatten() {
+                            ui.label(
+                                RichText::new(category.label())
+                                    .small()
+                                    .background_color(ui.visuals().faint_bg_color),
+                            );
+                        }
+                    });
+                }
+            });
+        }
+
+        if !removed_scenery_packs.is_empty() {
+            let heading = fl!(
+                "removed-scenery-packs-heading",
+                count = removed_scenery_packs.len()
+            );
+            ui.collapsing(heading, |ui| {
+                if ui
+                    .button(fl!("remove-all-scenery-packs-button"))
+                    .on_hover_text(fl!("remove-all-scenery-packs-hover-text"))
+                    .clicked()
+                {
+                    self.state
+                        .dispatch(ScenableAction::RemoveSceneryPacks(RemoveSceneryPacks {
+                            paths: removed_scenery_packs.to_vec(),
+                        }));
+                }
+                for path in removed_scenery_packs {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .small_button("🗑")
+                            .on_hover_text(fl!("remove-scenery-pack-hover-text"))
+                            .clicked()
+                        {
+                            self.state.dispatch(ScenableAction::RemoveSceneryPacks(
+                                RemoveSceneryPacks {
+                                    paths: vec![path.clone()],
+                                },
+                            ));
+                        }
+                        ui.label(scenery_pack_label(path));
+                    });
+                }
+            });
+        }
+    }
 }
 
 impl Page for SceneryPacksPage {
     type Response = ();
-    fn show(&mut self, ctx: &egui::CtxRef, _frame: &epi::Frame) -> Self::Response {
+    fn show(&mut self, ctx: &egui::CtxRef, frame: &epi::Frame) -> Self::Response {
+        if let Some(scan) = self.scanner.check() {
+            self.state
+                .dispatch(ScenableAction::UpdateSceneryScan(Rc::new(scan)));
+        }
+
+        if self.scan_required && !self.scanner.is_scanning() {
+            self.scan_required = false;
+            if let Err(error) = self.start_scan(frame) {
+                tracing::error!("Error while scanning scenery packs: {}", error);
+            }
+        }
+
+        let time = ctx.input().time;
+        if time - self.ini_checked_time >= INI_CHECK_INTERVAL {
+            self.ini_checked_time = time;
+            if let Err(error) = self.check_ini_modified() {
+                tracing::error!("Error while checking scenery_packs.ini: {}", error);
+            }
+        }
+
+        if self.external_ini.is_some() || !self.merge_messages.is_empty() {
+            egui::TopBottomPanel::top("external-changes-panel")
+                .show(ctx, |ui| self.external_changes_ui(ui));
+        }
+
+        if !self.invalid_lines_acknowledged {
+            egui::TopBottomPanel::top("invalid-lines-panel")
+                .show(ctx, |ui| self.invalid_lines_ui(ui));
+        }
+
+        if self.state.state().missing_global_airports() {
+            egui::TopBottomPanel::top("missing-entries-panel")
+                .show(ctx, |ui| self.missing_entries_ui(ui));
+        }
+
+        egui::SidePanel::right("presets-panel").show(ctx, |ui| {
+            self.presets_view.ui(ui, frame);
+            ui.separator();
+            ui.collapsing(fl!("area-heading"), |ui| self.area_view.ui(ui, frame));
+            ui.collapsing(fl!("route-heading"), |ui| self.route_view.ui(ui, frame));
+        });
+
+        let mut sort_open = self.sort_open;
+        egui::Window::new(fl!("sort-heading"))
+            .open(&mut sort_open)
+            .show(ctx, |ui| {
+                if self.sort_view.ui(ui, frame) {
+                    self.sort_open = false;
+                }
+            });
+        self.sort_open &= sort_open;
+
         let current_state = self.state.state();
         egui::CentralPanel::default().show(ctx, |ui| {
             let scroll_area = ScrollArea::vertical()
@@ -91,7 +794,7 @@ impl Page for SceneryPacksPage {
                     ui.horizontal(|ui| {
                         let prev_history = current_state.scenery_packs_history.peek_prev();
                         let response = ui.add_enabled(prev_history.is_some(), Button::new("⮪"));
-                        let response = if let Some(_) = prev_history {
+                        let response = if prev_history.is_some() {
                             let (current_history, _) =
                                 current_state.scenery_packs_history.peek_current();
                             response.on_hover_text(fl!(
@@ -121,10 +824,16 @@ impl Page for SceneryPacksPage {
 
                         let response = ui
                             .add_enabled(
-                                !current_state.scenery_packs_synchronized(),
+                                !current_state.scenery_packs_synchronized()
+                                    && self.invalid_lines_acknowledged,
                                 Button::new("💾"),
                             )
                             .on_hover_text(fl!("save-hover-text"));
+                        let response = if self.invalid_lines_acknowledged {
+                            response
+                        } else {
+                            response.on_disabled_hover_text(fl!("save-invalid-lines-hover-text"))
+                        };
 
                         if response.clicked() {
                             if let Err(error) = self.save_scenery_packs() {
@@ -132,14 +841,112 @@ impl Page for SceneryPacksPage {
                             }
                         }
 
-                        ()
+                        ui.checkbox(
+                            &mut self.manage_libraries,
+                            fl!("manage-libraries-checkbox-label"),
+                        )
+                        .on_hover_text(fl!("manage-libraries-hover-text"));
+
+                        let filter_text = match &self.category_filter {
+                            Some(category) => category.label(),
+                            None => fl!("category-filter-all"),
+                        };
+                        ComboBox::from_id_source("category-filter")
+                            .selected_text(filter_text)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut self.category_filter,
+                                    None,
+                                    fl!("category-filter-all"),
+                                );
+                                for category in SceneryCategory::ALL {
+                                    ui.selectable_value(
+                                        &mut self.category_filter,
+                                        Some(category),
+                                        category.label(),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(fl!("category-filter-hover-text"));
+
+                        if ui
+                            .button(fl!("sort-button"))
+                            .on_hover_text(fl!("sort-hover-text"))
+                            .clicked()
+                        {
+                            self.sort_open = true;
+                        }
+
+                        if let Some(xplane_version) = current_state.xplane_version {
+                            let ini_version = self.ini.version;
+                            ui.label(RichText::new(xplane_version.label()).small())
+                                .on_hover_text(fl!(
+                                    "scenery-packs-ini-version-hover-text",
+                                    version = ini_version
+                                ));
+                        }
+
+                        if self.scanner.is_scanning() {
+                            ui.label(fl!("scanning-scenery-packs-label"));
+                        } else if ui
+                            .button("🔄")
+                            .on_hover_text(fl!("rescan-hover-text"))
+                            .clicked()
+                        {
+                            self.scan_required = true;
+                        }
                     });
 
+                    self.selection_ui(ui, &current_state);
+
+                    let removed_scenery_packs = current_state.removed_scenery_packs();
+                    self.pack_changes_ui(ui, &current_state, &removed_scenery_packs);
+                    self.airport_conflicts_ui(ui, &current_state);
+
+                    let diff = current_state.scenery_packs_diff();
+                    self.pending_changes_ui(ui, &diff);
+                    let changes = diff.by_path();
+
                     current_state.scenery_packs.iter().enumerate().for_each(
                         |(index, scenery_pack)| {
-                            ui.horizontal(|ui| {
+                            let categories = current_state
+                                .scenery_scan
+                                .packs
+                                .get(&scenery_pack.path)
+                                .map(|pack_scan| &pack_scan.categories);
+
+                            if let Some(category_filter) = &self.category_filter {
+                                let matches_filter = categories
+                                    .map(|categories| categories.contains(category_filter))
+                                    .unwrap_or(false);
+                                if !matches_filter {
+                                    return;
+                                }
+                            }
+
+                            let selected = current_state.selection.is_selected(&scenery_pack.path);
+                            let row = ui.horizontal(|ui| {
+                                let handle = ui
+                                    .add(Label::new("☰").sense(Sense::drag()))
+                                    .on_hover_cursor(CursorIcon::Grab)
+                                    .on_hover_text(fl!("drag-scenery-packs-hover-text"));
+                                if handle.drag_started() {
+                                    self.dragging = true;
+                                    if !selected {
+                                        self.state.dispatch(ScenableAction::SelectSceneryPacks(
+                                            SelectSceneryPacks::Only { index },
+                                        ));
+                                    }
+                                }
+
                                 let mut enabled = scenery_pack.enabled;
-                                if ui.checkbox(&mut enabled, "").clicked() {
+                                if ui.checkbox(&mut enabled, "").clicked() && self.manage_libraries
+                                {
+                                    self.state.dispatch(ScenableAction::EnableSceneryPack(
+                                        EnableSceneryPack { index, enabled },
+                                    ))
+                                } else if enabled != scenery_pack.enabled {
                                     let mut new_scenery_pack = scenery_pack.clone();
                                     new_scenery_pack.enabled = enabled;
 
@@ -154,7 +961,7 @@ impl Page for SceneryPacksPage {
                                     } else {
                                         LocalizedString::new(move || {
                                             fl!(
-                                                "scenery-pack-enabled-operation",
+                                                "scenery-pack-disabled-operation",
                                                 path = path_debug.clone()
                                             )
                                         })
@@ -168,30 +975,175 @@ impl Page for SceneryPacksPage {
                                         },
                                     ))
                                 }
-                                ui.label(scenery_pack.path.to_string_lossy().to_string());
+                                let response = ui.selectable_label(
+                                    selected,
+                                    scenery_pack_label(&scenery_pack.path),
+                                );
+                                if response.clicked() {
+                                    let modifiers = ui.input().modifiers;
+                                    let action = if modifiers.shift {
+                                        SelectSceneryPacks::Range { index }
+                                    } else if modifiers.command {
+                                        SelectSceneryPacks::Toggle { index }
+                                    } else {
+                                        SelectSceneryPacks::Only { index }
+                                    };
+                                    self.state
+                                        .dispatch(ScenableAction::SelectSceneryPacks(action));
+                                }
+                                if let Some(hover_text) = dependencies_hover_text(
+                                    &current_state.dependencies,
+                                    &scenery_pack.path,
+                                ) {
+                                    response.on_hover_text(hover_text);
+                                }
+
+                                if removed_scenery_packs.contains(&scenery_pack.path) {
+                                    ui.label(
+                                        RichText::new(fl!("scenery-pack-missing-label"))
+                                            .small()
+                                            .color(Color32::RED),
+                                    )
+                                    .on_hover_text(fl!("scenery-pack-missing-hover-text"));
+                                }
+
+                                for category in categories.into_iter().flatten() {
+                                    ui.label(
+                                        RichText::new(category.label())
+                                            .small()
+                                            .background_color(ui.visuals().faint_bg_color),
+                                    );
+                                }
+
+                                if let Some(change) = changes.get(scenery_pack.path.as_path()) {
+                                    change_badges_ui(ui, change);
+                                }
+
+                                for tag in current_state.tags.get(&scenery_pack.path) {
+                                    ui.label(
+                                        RichText::new(format!("🏷 {}", tag))
+                                            .small()
+                                            .background_color(ui.visuals().faint_bg_color),
+                                    );
+                                }
                             });
+
+                            if self.dragging {
+                                self.drag_target_ui(ui, row.response.rect, index);
+                            }
                         },
-                    )
+                    );
+
+                    if self.dragging && ui.input().pointer.any_released() {
+                        if let Some(index) = self.drop_index {
+                            self.state.dispatch(ScenableAction::MoveSelected(
+                                MoveSelected::ToIndex(index),
+                            ));
+                        }
+                        self.dragging = false;
+                        self.drop_index = None;
+                    }
                 })
             })
         });
     }
 }
 
-fn read_scenery_packs_ini(ini_path: impl AsRef<Path>) -> eyre::Result<SceneryPacksIni> {
-    tracing::info!("Reading scenery packs from {:?}", ini_path.as_ref());
-    let ini_file_string = std::fs::read_to_string(&ini_path)?;
-    let (_, ini) = scenery_packs_ini(&ini_file_string)
-        .map_err(|error| eyre::eyre!("Error parsing {:?}: {}", ini_path.as_ref(), error))?;
-    Ok(ini.clone())
+/// Show coloured badges describing how a scenery pack has changed since
+/// `scenery_packs.ini` was last read or saved.
+pub fn change_badges_ui(ui: &mut egui::Ui, change: &SceneryPackChange) {
+    let mut badges: Vec<(String, Color32)> = Vec::new();
+    if change.is_added() {
+        badges.push((fl!("change-added-label"), Color32::GREEN));
+    }
+    if change.is_removed() {
+        badges.push((fl!("change-removed-label"), Color32::RED));
+    }
+    if !change.is_added() {
+        match change.enabled {
+            Some(true) => badges.push((fl!("change-enabled-label"), Color32::GREEN)),
+            Some(false) => badges.push((fl!("change-disabled-label"), Color32::RED)),
+            None => {}
+        }
+    }
+    if let (true, Some(saved_index), Some(index)) = (change.moved, change.saved_index, change.index)
+    {
+        let (from, to) = (saved_index + 1, index + 1);
+        badges.push((
+            fl!("change-moved-label", from = from, to = to),
+            Color32::YELLOW,
+        ));
+    }
+
+    for (text, color) in badges {
+        ui.label(RichText::new(text).small().color(color))
+            .on_hover_text(fl!("change-hover-text"));
+    }
+}
+
+/// The name to display for the scenery pack with `path` (see
+/// [SceneryPack::path]).
+pub fn scenery_pack_label(path: &Path) -> String {
+    match SceneryPackPath::new(path) {
+        SceneryPackPath::Relative(path) => path.to_string_lossy().to_string(),
+        SceneryPackPath::Absolute(path) => fl!(
+            "absolute-scenery-pack-label",
+            path = path.to_string_lossy().to_string()
+        ),
+        SceneryPackPath::GlobalAirports => fl!("global-airports-label"),
+        SceneryPackPath::Special(name) => name.to_owned(),
+    }
+}
+
+/// Describe the dependencies of the scenery pack at `path`, or `None`
+/// if it has no known dependencies or dependents.
+fn dependencies_hover_text(dependencies: &DependencyGraph, path: &Path) -> Option<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    let mut add_section = |title: String, paths_by_pack: Option<&PathsByPack>| {
+        if let Some(paths_by_pack) = paths_by_pack {
+            lines.push(title);
+            lines.extend(paths_by_pack.iter().map(|(pack, paths)| {
+                fl!(
+                    "dependency-hover-line",
+                    pack = pack.to_string_lossy().to_string(),
+                    count = paths.len()
+                )
+            }));
+        }
+    };
+
+    add_section(fl!("requires-libraries-label"), dependencies.requires(path));
+    add_section(fl!("required-by-label"), dependencies.required_by(path));
+
+    if let Some(unresolved) = dependencies.unresolved(path) {
+        lines.push(fl!("unresolved-paths-label", count = unresolved.len()));
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
 }
 
-fn write_scenery_packs_ini(ini: &SceneryPacksIni, ini_path: impl AsRef<Path>) -> eyre::Result<()> {
-    tracing::info!("Writing scenery packs to {:?}", ini_path.as_ref());
-    let mut file = std::fs::OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open(ini_path)?;
-    ini.write_ini(&mut file)?;
-    Ok(())
+/// Write `scenery_packs.ini` atomically (see [write_atomic()]), after
+/// backing up the previous version and keeping up to `backup_count`
+/// backups.
+fn write_scenery_packs_ini(
+    ini: &SceneryPacksIni,
+    ini_path: &Path,
+    backup_count: usize,
+) -> eyre::Result<()> {
+    tracing::info!("Writing scenery packs to {:?}", ini_path);
+    let mut buffer: Vec<u8> = Vec::new();
+    ini.write_ini(&mut buffer)?;
+
+    if let Err(error) =
+        Backups::from_data_dir(ini_path).and_then(|backups| backups.create(ini_path, backup_count))
+    {
+        tracing::error!("Error while backing up {:?}: {}", ini_path, error);
+    }
+
+    write_atomic(ini_path, &buffer)
 }
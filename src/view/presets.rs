@@ -0,0 +1,134 @@
+use egui::{Button, TextEdit};
+
+use crate::{
+    fl,
+    state::{PresetAction, ScenableAction, ScenableStateRef},
+    view::View,
+};
+
+/// A [View] listing the user's presets, with controls to create,
+/// apply, update, rename, duplicate and delete them. Changes to the
+/// presets are saved to disk immediately.
+pub struct PresetsView {
+    state: ScenableStateRef,
+    /// Name for the next preset to be created.
+    new_name: String,
+    /// The preset currently being renamed, and its new name.
+    renaming: Option<(String, String)>,
+}
+
+impl PresetsView {
+    pub fn new(state: ScenableStateRef) -> Self {
+        Self {
+            state,
+            new_name: String::new(),
+            renaming: None,
+        }
+    }
+
+    fn dispatch(&self, action: PresetAction) {
+        self.state.dispatch(ScenableAction::Preset(action));
+    }
+}
+
+impl View for PresetsView {
+    type Response = ();
+
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &epi::Frame) -> Self::Response {
+        let current_state = self.state.state();
+
+        ui.heading(fl!("presets-heading"));
+
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.new_name).hint_text(fl!("preset-name-hint")));
+            let name = self.new_name.trim();
+            let valid_name = !name.is_empty() && !current_state.presets.contains(name);
+            if ui
+                .add_enabled(valid_name, Button::new(fl!("create-preset-button")))
+                .on_hover_text(fl!("create-preset-hover-text"))
+                .clicked()
+            {
+                self.dispatch(PresetAction::Create {
+                    name: name.to_string(),
+                });
+                self.new_name.clear();
+            }
+        });
+
+        ui.separator();
+
+        for preset in &current_state.presets.presets {
+            ui.horizontal(|ui| {
+                if let Some((name, new_name)) = &mut self.renaming {
+                    if *name == preset.name {
+                        let response = ui.text_edit_singleline(new_name);
+                        if response.lost_focus() {
+                            let new_name = new_name.trim().to_string();
+                            let name = name.clone();
+                            self.renaming = None;
+                            if ui.input().key_pressed(egui::Key::Enter)
+                                && !new_name.is_empty()
+                                && new_name != name
+                            {
+                                self.dispatch(PresetAction::Rename { name, new_name });
+                            }
+                        } else {
+                            response.request_focus();
+                        }
+                        return;
+                    }
+                }
+
+                if ui
+                    .button("▶")
+                    .on_hover_text(fl!("apply-preset-hover-text"))
+                    .clicked()
+                {
+                    self.dispatch(PresetAction::Apply {
+                        name: preset.name.clone(),
+                    });
+                }
+                ui.label(&preset.name);
+
+                if ui
+                    .button("⟲")
+                    .on_hover_text(fl!("update-preset-hover-text"))
+                    .clicked()
+                {
+                    self.dispatch(PresetAction::Update {
+                        name: preset.name.clone(),
+                    });
+                }
+                if ui
+                    .button("✏")
+                    .on_hover_text(fl!("rename-preset-hover-text"))
+                    .clicked()
+                {
+                    self.renaming = Some((preset.name.clone(), preset.name.clone()));
+                }
+                if ui
+                    .button("🗐")
+                    .on_hover_text(fl!("duplicate-preset-hover-text"))
+                    .clicked()
+                {
+                    let new_name = current_state
+                        .presets
+                        .unique_name(&fl!("preset-copy-name", name = preset.name.clone()));
+                    self.dispatch(PresetAction::Duplicate {
+                        name: preset.name.clone(),
+                        new_name,
+                    });
+                }
+                if ui
+                    .button("🗑")
+                    .on_hover_text(fl!("delete-preset-hover-text"))
+                    .clicked()
+                {
+                    self.dispatch(PresetAction::Delete {
+                        name: preset.name.clone(),
+                    });
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+
+use egui::{Color32, DragValue, TextEdit};
+use im_native_dialog::ImNativeFileDialog;
+
+use crate::{
+    fl,
+    geo::{GeoArea, LatLon},
+    route::{parse_route, read_fms, Fixes},
+    state::{EnableArea, ScenableAction, ScenableStateRef},
+    view::View,
+};
+
+/// A [View] for entering a route, or reading one from a flight plan,
+/// and enabling only the scenery packs which cover a corridor along
+/// it.
+pub struct RouteView {
+    state: ScenableStateRef,
+    route: String,
+    /// The path and waypoints of the flight plan which was read, which
+    /// is used instead of `route` if present.
+    fms: Option<(PathBuf, Vec<LatLon>)>,
+    fms_dialog: ImNativeFileDialog<Option<PathBuf>>,
+    width_nm: f64,
+    /// Fixes from X-Plane's navigation data, read the first time they
+    /// are needed.
+    fixes: Option<Fixes>,
+    error: Option<String>,
+}
+
+impl RouteView {
+    pub fn new(state: ScenableStateRef) -> Self {
+        Self {
+            state,
+            route: String::new(),
+            fms: None,
+            fms_dialog: ImNativeFileDialog::default(),
+            width_nm: 20.0,
+            fixes: None,
+            error: None,
+        }
+    }
+
+    fn route_locations(&mut self) -> eyre::Result<Vec<LatLon>> {
+        if let Some((_, locations)) = &self.fms {
+            return Ok(locations.clone());
+        }
+
+        let current_state = self.state.state();
+        let fixes = match &mut self.fixes {
+            Some(fixes) => fixes,
+            None => {
+                let xplane_dir = current_state.settings.xplane_dir.as_ref().ok_or_else(|| {
+                    eyre::eyre!("xplane_dir needs to be set before reading navigation data")
+                })?;
+                self.fixes.insert(Fixes::read(xplane_dir)?)
+            }
+        };
+        parse_route(&self.route, &current_state.scenery_scan, fixes)
+    }
+}
+
+impl View for RouteView {
+    type Response = ();
+
+    fn ui(&mut self, ui: &mut egui::Ui, frame: &epi::Frame) -> Self::Response {
+        if let Some(result) = self.fms_dialog.check() {
+            match result {
+                Ok(Some(path)) => match read_fms(&path) {
+                    Ok(locations) => {
+                        self.fms = Some((path, locations));
+                        self.error = None;
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                },
+                Ok(None) => {}
+                Err(error) => {
+                    tracing::error!("Error selecting flight plan: {}", error)
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.add_enabled(
+                self.fms.is_none(),
+                TextEdit::multiline(&mut self.route)
+                    .desired_rows(2)
+                    .hint_text(fl!("route-hint")),
+            );
+            if ui
+                .add_enabled(!self.fms_dialog.is_open(), egui::Button::new("📂"))
+                .on_hover_text(fl!("route-open-fms-hover-text"))
+                .clicked()
+            {
+                let xplane_dir = self.state.state().settings.xplane_dir.clone();
+                let dialog_frame = frame.clone();
+                self.fms_dialog
+                    .with_callback(move |_| dialog_frame.request_repaint())
+                    .open_single_file(xplane_dir.map(|dir| dir.join("Output").join("FMS plans")))
+                    .expect("Unable to open flight plan dialog");
+            }
+        });
+
+        if let Some((path, locations)) = &self.fms {
+            let file = path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut clear = false;
+            ui.horizontal(|ui| {
+                ui.label(fl!("route-fms-label", file = file, count = locations.len()));
+                clear = ui
+                    .small_button("✖")
+                    .on_hover_text(fl!("route-clear-fms-hover-text"))
+                    .clicked();
+            });
+            if clear {
+                self.fms = None;
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(fl!("route-width-label"));
+            ui.add(
+                DragValue::new(&mut self.width_nm)
+                    .speed(1.0)
+                    .clamp_range(0.0..=1000.0),
+            );
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        if ui
+            .button(fl!("route-enable-button"))
+            .on_hover_text(fl!("route-enable-hover-text"))
+            .clicked()
+        {
+            match self.route_locations() {
+                Ok(route) => {
+                    self.error = None;
+                    self.state.dispatch(ScenableAction::EnableArea(EnableArea {
+                        area: GeoArea::Corridor {
+                            route,
+                            width_nm: self.width_nm,
+                        },
+                    }));
+                }
+                Err(error) => self.error = Some(error.to_string()),
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use egui::{Grid, ScrollArea};
+
+use crate::{
+    classify::{sort_scenery_packs, SceneryCategory, SortRule},
+    fl,
+    state::{ScenableAction, ScenableStateRef},
+    view::{page::scenery_packs::scenery_pack_label, View},
+};
+
+/// A [View] for editing the rules used to sort the load order of
+/// scenery packs, previewing the result, and applying it.
+pub struct SortView {
+    state: ScenableStateRef,
+}
+
+impl SortView {
+    pub fn new(state: ScenableStateRef) -> Self {
+        Self { state }
+    }
+
+    /// Save the new sort rules to the [Settings](crate::settings::Settings).
+    fn update_rules(&self, rules: Vec<SortRule>) {
+        let mut settings = (*self.state.state().settings).clone();
+        settings.sort_rules = rules;
+        if let Err(error) = settings.save() {
+            tracing::error!("Error while saving settings: {}", error);
+        }
+        self.state
+            .dispatch(ScenableAction::UpdateSettings(settings));
+    }
+
+    fn rules_ui(&self, ui: &mut egui::Ui, rules: &[SortRule]) {
+        let mut new_rules: Option<Vec<SortRule>> = None;
+
+        for (index, rule) in rules.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}. {}", index + 1, rule.label()));
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("⬆").small())
+                    .on_hover_text(fl!("sort-rule-up-hover-text"))
+                    .clicked()
+                {
+                    let mut rules = rules.to_vec();
+                    rules.swap(index - 1, index);
+                    new_rules = Some(rules);
+                }
+                if ui
+                    .add_enabled(index + 1 < rules.len(), egui::Button::new("⬇").small())
+                    .on_hover_text(fl!("sort-rule-down-hover-text"))
+                    .clicked()
+                {
+                    let mut rules = rules.to_vec();
+                    rules.swap(index, index + 1);
+                    new_rules = Some(rules);
+                }
+                if ui
+                    .small_button("✖")
+                    .on_hover_text(fl!("sort-rule-remove-hover-text"))
+                    .clicked()
+                {
+                    let mut rules = rules.to_vec();
+                    rules.remove(index);
+                    new_rules = Some(rules);
+                }
+            });
+        }
+
+        let missing_rules: Vec<SortRule> = SceneryCategory::ALL
+            .iter()
+            .copied()
+            .map(SortRule::Category)
+            .chain(std::iter::once(SortRule::GlobalAirports))
+            .filter(|rule| !rules.contains(rule))
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!missing_rules.is_empty(), |ui| {
+                ui.menu_button(fl!("sort-rule-add-button"), |ui| {
+                    for rule in &missing_rules {
+                        if ui.button(rule.label()).clicked() {
+                            let mut rules = rules.to_vec();
+                            rules.push(*rule);
+                            new_rules = Some(rules);
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+            if ui
+                .button(fl!("sort-rules-reset-button"))
+                .on_hover_text(fl!("sort-rules-reset-hover-text"))
+                .clicked()
+            {
+                new_rules = Some(SortRule::DEFAULT.to_vec());
+            }
+        });
+
+        if let Some(new_rules) = new_rules {
+            self.update_rules(new_rules);
+        }
+    }
+}
+
+impl View for SortView {
+    /// Whether the view should be closed, because the sort was applied
+    /// or cancelled.
+    type Response = bool;
+
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &epi::Frame) -> Self::Response {
+        let current_state = self.state.state();
+        let rules = &current_state.settings.sort_rules;
+
+        ui.heading(fl!("sort-rules-heading"));
+        self.rules_ui(ui, rules);
+
+        ui.separator();
+
+        let sorted = sort_scenery_packs(
+            &current_state.scenery_packs,
+            &current_state.scenery_scan,
+            rules,
+        );
+        let prev_positions: HashMap<_, usize> = current_state
+            .scenery_packs
+            .iter()
+            .enumerate()
+            .map(|(index, scenery_pack)| (&scenery_pack.path, index))
+            .collect();
+        let moved: Vec<(usize, usize, String)> = sorted
+            .iter()
+            .enumerate()
+            .filter_map(|(index, scenery_pack)| {
+                let prev_index = *prev_positions.get(&scenery_pack.path)?;
+                (prev_index != index)
+                    .then(|| (prev_index, index, scenery_pack_label(&scenery_pack.path)))
+            })
+            .collect();
+
+        if moved.is_empty() {
+            ui.label(fl!("sort-preview-unchanged-label"));
+        } else {
+            ui.label(fl!("sort-preview-heading", count = moved.len()));
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                Grid::new("sort-preview-grid").striped(true).show(ui, |ui| {
+                    for (prev_index, index, path) in &moved {
+                        ui.label(format!("{} → {}", prev_index + 1, index + 1));
+                        ui.label(path);
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+
+        ui.separator();
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !moved.is_empty(),
+                    egui::Button::new(fl!("sort-apply-button")),
+                )
+                .on_hover_text(fl!("sort-apply-hover-text"))
+                .clicked()
+            {
+                self.state.dispatch(ScenableAction::SortSceneryPacks);
+                close = true;
+            }
+            if ui.button(fl!("sort-cancel-button")).clicked() {
+                close = true;
+            }
+        });
+        close
+    }
+}
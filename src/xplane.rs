@@ -0,0 +1,88 @@
+//! Differences between the versions of X-Plane which affect how its
+//! scenery is arranged.
+
+use std::path::{Path, PathBuf};
+
+/// The major version of an X-Plane installation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XPlaneVersion {
+    X11,
+    X12,
+}
+
+impl XPlaneVersion {
+    /// Detect the version of the X-Plane installation at `xplane_dir`
+    /// from where its global airports are kept, or `None` if it cannot
+    /// be determined.
+    pub fn detect(xplane_dir: &Path) -> Option<Self> {
+        [XPlaneVersion::X12, XPlaneVersion::X11]
+            .iter()
+            .copied()
+            .find(|version| version.global_airports_dir(xplane_dir).is_dir())
+    }
+
+    /// The directory containing the global airports, which the
+    /// `*GLOBAL_AIRPORTS*` entry in `scenery_packs.ini` refers to.
+    /// X-Plane 12 keeps them in `Global Scenery`, X-Plane 11 in `Custom
+    /// Scenery`.
+    pub fn global_airports_dir(&self, xplane_dir: &Path) -> PathBuf {
+        let parent = match self {
+            XPlaneVersion::X11 => "Custom Scenery",
+            XPlaneVersion::X12 => "Global Scenery",
+        };
+        xplane_dir.join(parent).join("Global Airports")
+    }
+
+    /// Whether `scenery_packs.ini` must contain the `*GLOBAL_AIRPORTS*`
+    /// entry. X-Plane 11 may instead list `Custom Scenery/Global
+    /// Airports/` like any other scenery pack, but X-Plane 12 keeps
+    /// its global airports outside of `Custom Scenery`, so they are
+    /// only loaded through that entry.
+    pub fn requires_global_airports_entry(&self) -> bool {
+        match self {
+            XPlaneVersion::X11 => false,
+            XPlaneVersion::X12 => true,
+        }
+    }
+
+    /// The directories containing the libraries which ship with
+    /// X-Plane, and are always available.
+    pub fn default_library_dirs(&self, xplane_dir: &Path) -> Vec<PathBuf> {
+        let default_scenery = xplane_dir.join("Resources").join("default scenery");
+        match self {
+            XPlaneVersion::X11 => vec![default_scenery],
+            // X-Plane 12 also ships libraries alongside its global
+            // scenery, such as those used by the global airports.
+            XPlaneVersion::X12 => vec![default_scenery, xplane_dir.join("Global Scenery")],
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            XPlaneVersion::X11 => "X-Plane 11",
+            XPlaneVersion::X12 => "X-Plane 12",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::XPlaneVersion;
+
+    #[test]
+    fn test_detect() {
+        let xplane_dir = tempfile::tempdir().unwrap();
+        let xplane_dir = xplane_dir.path();
+        assert_eq!(None, XPlaneVersion::detect(xplane_dir));
+
+        let custom_airports = xplane_dir.join("Custom Scenery").join("Global Airports");
+        std::fs::create_dir_all(&custom_airports).unwrap();
+        assert_eq!(Some(XPlaneVersion::X11), XPlaneVersion::detect(xplane_dir));
+
+        std::fs::remove_dir_all(&custom_airports).unwrap();
+        std::fs::create_dir_all(xplane_dir.join("Global Scenery").join("Global Airports")).unwrap();
+        assert_eq!(Some(XPlaneVersion::X12), XPlaneVersion::detect(xplane_dir));
+    }
+}